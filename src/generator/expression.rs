@@ -0,0 +1,410 @@
+use std::error::Error;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::generator::gen::Generator;
+use crate::generator::iterative::{IterativeGen, Letters, Schedule};
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::notation::ParseCycleError;
+use crate::group_theory::presentation::PresentationError;
+
+/// Why a trace expression, or one of its permutations, couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionError {
+    /// The notation given for this name isn't a permutation of the ground set.
+    Permutation {
+        name: String,
+        error: ParseCycleError,
+    },
+    /// A name that can't be written in an expression, ie "2a" or "1".
+    InvalidName(String),
+    /// The permutation given for this name is over a different ground set than the expression.
+    GroundMismatch(String),
+    /// The same name was defined twice.
+    Redefined(String),
+    /// A definition without the '=' in "name = notation".
+    Definition(String),
+    /// The expression itself doesn't parse, or uses a name that wasn't defined.
+    Expression(PresentationError),
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpressionError::Permutation { name, error } => write!(f, "{}: {}", name, error),
+            ExpressionError::InvalidName(name) => write!(f, "{:?} can't be used as a name", name),
+            ExpressionError::GroundMismatch(name) => {
+                write!(f, "{} isn't over the ground set of the expression", name)
+            }
+            ExpressionError::Redefined(name) => write!(f, "{} is defined twice", name),
+            ExpressionError::Definition(s) => {
+                write!(f, "expected \"name = permutation\", found {:?}", s)
+            }
+            ExpressionError::Expression(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ExpressionError {}
+
+impl From<PresentationError> for ExpressionError {
+    fn from(e: PresentationError) -> Self {
+        ExpressionError::Expression(e)
+    }
+}
+
+/// Named permutations of a ground set, and expressions over them describing a trace. Every letter of an expression
+/// is one pass: "(a b)^3 c a^-1" starts from the ground set and applies a, b, a, b, a, b, c and the inverse of a in
+/// turn, each to the pass before. Letters are separated by whitespace or '*', "^k" repeats and "^-1" inverts
+/// (the letters of a group are inverted in reverse order), and "1" is no pass at all. Powers are kept as repeats,
+/// so "(a b)^1000000000" compiles at once and its passes are only made as they are walked.
+pub struct TraceExpression<V>
+where
+    V: Clone + Hash + Eq + 'static,
+{
+    ground: GroundSet<V>,
+    names: Vec<String>,
+    permutations: Vec<Cycle<V>>,
+}
+
+impl<V> TraceExpression<V>
+where
+    V: Debug + Clone + Eq + Hash + FromStr + Send + Sync + 'static,
+{
+    pub fn new(ground: impl Into<GroundSet<V>>) -> Self {
+        TraceExpression {
+            ground: ground.into(),
+            names: Vec::new(),
+            permutations: Vec::new(),
+        }
+    }
+
+    /// Names a permutation given in any notation Cycle::parse reads, ie "(1,2,3)" or "3,1,2".
+    pub fn define(&mut self, name: &str, notation: &str) -> Result<(), ExpressionError> {
+        let cycle =
+            Cycle::parse(notation, &self.ground).map_err(|error| ExpressionError::Permutation {
+                name: name.to_string(),
+                error,
+            })?;
+        self.define_cycle(name, cycle)
+    }
+
+    /// Names a permutation that is already built, which has to be over the same ground set.
+    pub fn define_cycle(&mut self, name: &str, cycle: Cycle<V>) -> Result<(), ExpressionError> {
+        let mut chars = name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && chars.all(|c| c.is_alphanumeric() || c == '_');
+        if !valid {
+            return Err(ExpressionError::InvalidName(name.to_string()));
+        }
+        if self.names.iter().any(|n| n == name) {
+            return Err(ExpressionError::Redefined(name.to_string()));
+        }
+        if cycle.ground_set() != &self.ground {
+            return Err(ExpressionError::GroundMismatch(name.to_string()));
+        }
+        self.names.push(name.to_string());
+        self.permutations.push(cycle);
+        Ok(())
+    }
+
+    pub fn permutation(&self, name: &str) -> Option<&Cycle<V>> {
+        let i = self.names.iter().position(|n| n == name)?;
+        Some(&self.permutations[i])
+    }
+
+    /// The permutation applied by each pass after the start, made as the passes are walked.
+    pub fn passes(
+        &self,
+        expression: &str,
+    ) -> Result<impl Iterator<Item = Cycle<V>> + '_, ExpressionError> {
+        Ok(
+            Letters::new(self.schedule(expression)?, 1).map(|letter| match letter % 2 {
+                0 => self.permutations[letter / 2].clone(),
+                _ => self.permutations[letter / 2].inverse(),
+            }),
+        )
+    }
+
+    /// A generator giving the trace of the expression, starting from the ground set. Every name gets two functions,
+    /// 2i for the permutation and 2i + 1 for its inverse, and the expression becomes the schedule.
+    pub fn compile(&self, expression: &str) -> Result<IterativeGen<V>, ExpressionError> {
+        let schedule = self.schedule(expression)?;
+        let mut generator = IterativeGen::new();
+        generator.set_start(&self.ground.to_vec());
+        for cycle in self.permutations.iter() {
+            generator.add(cycle.get_function());
+            generator.add(cycle.inverse().get_function());
+        }
+        generator.set_nested_schedule(schedule);
+        Ok(generator)
+    }
+
+    /// A whole experiment in one string: definitions "name = permutation" separated by ';', then the expression,
+    /// ie "a = (1,2,3); b = 3,1,2,4; (a b)^3 b a^-1" on 1..=4.
+    pub fn parse(
+        spec: &str,
+        ground: impl Into<GroundSet<V>>,
+    ) -> Result<IterativeGen<V>, ExpressionError> {
+        let mut parts: Vec<&str> = spec.split(';').collect();
+        let expression = parts.pop().unwrap_or_default();
+        let mut trace = TraceExpression::new(ground);
+        for definition in parts {
+            let Some((name, notation)) = definition.split_once('=') else {
+                return Err(ExpressionError::Definition(definition.trim().to_string()));
+            };
+            trace.define(name.trim(), notation)?;
+        }
+        trace.compile(expression)
+    }
+
+    /// The expression as a schedule, with name i as the letter 2i and its inverse as 2i + 1.
+    fn schedule(&self, expression: &str) -> Result<Vec<Schedule>, ExpressionError> {
+        let mut parser = ScheduleParser {
+            chars: expression.chars().collect(),
+            position: 0,
+            names: &self.names,
+        };
+        let schedule = parser.expression()?;
+        match parser.chars.get(parser.position) {
+            None => Ok(schedule),
+            Some(_) => Err(parser.malformed("unexpected character").into()),
+        }
+    }
+}
+
+/// The most times a name or group can be repeated, either way.
+pub const MAX_EXPONENT: u64 = 1 << 32;
+
+/// The schedule running the inverse of every pass in reverse order, so the letters 2i and 2i + 1 swap.
+fn invert(schedule: &[Schedule]) -> Vec<Schedule> {
+    schedule
+        .iter()
+        .rev()
+        .map(|step| match step {
+            Schedule::Letter(letter) => Schedule::Letter(letter ^ 1),
+            Schedule::Repeat(body, k) => Schedule::Repeat(invert(body), *k),
+        })
+        .collect()
+}
+
+/// Recursive descent over
+///   expression := factor*
+///   factor     := atom ('^' integer)?
+///   atom       := name | '1' | '(' expression ')'
+/// with whitespace or '*' between factors. This is the grammar of presentation words without '=', but a power
+/// becomes a repeat instead of being written out.
+struct ScheduleParser<'a> {
+    chars: Vec<char>,
+    position: usize,
+    names: &'a [String],
+}
+
+impl ScheduleParser<'_> {
+    fn malformed(&self, reason: &str) -> PresentationError {
+        PresentationError::Malformed {
+            position: self.position,
+            reason: reason.to_string(),
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.chars.get(self.position).is_some_and(|&c| f(c)) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn expression(&mut self) -> Result<Vec<Schedule>, PresentationError> {
+        let mut schedule = Vec::new();
+        loop {
+            self.take_while(|c| c.is_whitespace() || c == '*');
+            match self.chars.get(self.position) {
+                None | Some(')') => return Ok(schedule),
+                Some(_) => schedule.extend(self.factor()?),
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<Vec<Schedule>, PresentationError> {
+        let atom = self.atom()?;
+        self.take_while(char::is_whitespace);
+        if self.chars.get(self.position) != Some(&'^') {
+            return Ok(atom);
+        }
+        self.position += 1;
+        self.take_while(char::is_whitespace);
+        let digits = self.take_while(|c| c == '-' || c.is_ascii_digit());
+        let Ok(power) = digits.parse::<i64>() else {
+            return Err(self.malformed("expected an integer exponent after '^'"));
+        };
+        if power.unsigned_abs() > MAX_EXPONENT {
+            return Err(self.malformed("exponent above 2^32"));
+        }
+        let body = if power < 0 { invert(&atom) } else { atom };
+        Ok(match power.unsigned_abs() {
+            1 => body,
+            k => vec![Schedule::Repeat(body, k as usize)],
+        })
+    }
+
+    fn atom(&mut self) -> Result<Vec<Schedule>, PresentationError> {
+        match self.chars[self.position] {
+            '(' => {
+                self.position += 1;
+                let schedule = self.expression()?;
+                if self.chars.get(self.position) != Some(&')') {
+                    return Err(self.malformed("unclosed '('"));
+                }
+                self.position += 1;
+                Ok(schedule)
+            }
+            '1' => {
+                self.position += 1;
+                Ok(Vec::new())
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                match self.names.iter().position(|n| *n == name) {
+                    Some(i) => Ok(vec![Schedule::Letter(2 * i)]),
+                    None => Err(PresentationError::UnknownGenerator(name)),
+                }
+            }
+            _ => Err(self.malformed("expected a name, '1' or '('")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::expression::{ExpressionError, TraceExpression};
+    use crate::generator::gen::Generator;
+    use crate::generator::periodic::PeriodicGen;
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::presentation::PresentationError;
+
+    fn rotate_and_swap() -> TraceExpression<usize> {
+        let mut trace = TraceExpression::new(vec![1, 2, 3, 4]);
+        trace.define("a", "(1,2,3,4)").unwrap();
+        trace.define("b", "2,1,3,4").unwrap();
+        trace
+    }
+
+    #[test]
+    fn expression_passes() {
+        let trace = rotate_and_swap();
+        let a = trace.permutation("a").unwrap().clone();
+        let b = trace.permutation("b").unwrap().clone();
+        debug_assert_eq!(b, Cycle::from(vec![vec![1, 2]], a.ground_set()));
+        let c = &a * &b;
+        let mut trace = trace;
+        trace.define_cycle("c", c.clone()).unwrap();
+        let passes: Vec<Cycle<usize>> = trace.passes("(a b)^3 c a^-1").unwrap().collect();
+        let expected = [&a, &b, &a, &b, &a, &b, &c, &a.inverse()];
+        debug_assert_eq!(passes.len(), expected.len());
+        debug_assert!(passes.iter().zip(expected).all(|(p, e)| p == e));
+        debug_assert_eq!(
+            trace.passes("(a b)^-1").unwrap().collect::<Vec<_>>(),
+            vec![b.inverse(), a.inverse()]
+        );
+        debug_assert!(trace.passes("1").unwrap().next().is_none());
+    }
+
+    #[test]
+    fn compiled_trace() {
+        let trace = rotate_and_swap();
+        let generator = trace.compile("(a b)^2").unwrap();
+        let mut periodic = PeriodicGen::new();
+        periodic.set_start(&[1, 2, 3, 4]);
+        periodic.add(trace.permutation("a").unwrap().get_function());
+        periodic.add(trace.permutation("b").unwrap().get_function());
+        debug_assert_eq!(generator.simulate(4), periodic.simulate(4));
+        debug_assert_eq!(generator.iter().count(), 5);
+        // a then its inverse comes back to the start
+        let there_and_back: Vec<Vec<usize>> = trace.compile("a a^-1").unwrap().iter().collect();
+        debug_assert_eq!(there_and_back[0], there_and_back[2]);
+        debug_assert_ne!(there_and_back[0], there_and_back[1]);
+    }
+
+    #[test]
+    fn huge_powers() {
+        let trace = rotate_and_swap();
+        let generator = trace.compile("(a b)^1000000000 a^-1").unwrap();
+        let short = trace.compile("(a b)^250").unwrap();
+        debug_assert_eq!(generator.simulate(6), short.simulate(6));
+        debug_assert!(generator
+            .accesses()
+            .take(2000)
+            .eq(short.accesses().take(2000)));
+        // the inverse of a power is the power of the inverse
+        debug_assert!(trace
+            .passes("(a b^2)^-2")
+            .unwrap()
+            .eq(trace.passes("b^-2 a^-1 b^-1 b^-1 a^-1").unwrap()));
+        // only the passes walked get made
+        let b = trace.permutation("b").unwrap();
+        let mut passes = trace.passes("(a b)^1000000000").unwrap();
+        debug_assert_eq!(passes.nth(20_001).as_ref(), Some(b));
+    }
+
+    #[test]
+    fn one_string() {
+        let generator =
+            TraceExpression::parse("a = (1,2,3); b = 3,2,1; (a b)^3 b a^-1", vec![1, 2, 3])
+                .unwrap();
+        debug_assert_eq!(generator.iter().count(), 9);
+        let mut trace = TraceExpression::new(vec![1, 2, 3]);
+        trace.define("a", "(1,2,3)").unwrap();
+        trace.define("b", "3,2,1").unwrap();
+        debug_assert_eq!(
+            generator.simulate(8),
+            trace.compile("a b a b a b b a^-1").unwrap().simulate(8)
+        );
+    }
+
+    #[test]
+    fn expression_errors() {
+        let mut trace = rotate_and_swap();
+        debug_assert_eq!(
+            trace.define("a", "(1,2)"),
+            Err(ExpressionError::Redefined("a".to_string()))
+        );
+        debug_assert!(matches!(
+            trace.define("c", "(1,5)"),
+            Err(ExpressionError::Permutation { .. })
+        ));
+        debug_assert_eq!(
+            trace.define_cycle("c", Cycle::from(vec![vec![1, 5]], vec![1, 2, 3, 4, 5])),
+            Err(ExpressionError::GroundMismatch("c".to_string()))
+        );
+        debug_assert_eq!(
+            trace.define_cycle("c", Cycle::from(vec![vec![1, 2]], vec![2, 1, 3, 4])),
+            Err(ExpressionError::GroundMismatch("c".to_string()))
+        );
+        debug_assert_eq!(
+            trace.define("2c", "(1,2)"),
+            Err(ExpressionError::InvalidName("2c".to_string()))
+        );
+        debug_assert_eq!(
+            trace.compile("a c").err(),
+            Some(ExpressionError::Expression(
+                PresentationError::UnknownGenerator("c".to_string())
+            ))
+        );
+        debug_assert!(trace.compile("(a b").is_err());
+        debug_assert!(trace.compile("a = b").is_err());
+        debug_assert!(matches!(
+            trace.compile("(a)^10000000000000").err(),
+            Some(ExpressionError::Expression(
+                PresentationError::Malformed { .. }
+            ))
+        ));
+        debug_assert_eq!(
+            TraceExpression::parse("a (1,2); a", vec![1, 2]).err(),
+            Some(ExpressionError::Definition("a (1,2)".to_string()))
+        );
+    }
+}
@@ -1,8 +1,167 @@
+use crate::group_theory::action::{elementwise, transform, Evaluate, GroupAction, Transform};
+use rand::distributions::WeightedIndex;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io;
+
+/// Why a generator can't make a trace, or can't change the function asked for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeneratorError {
+    /// Nothing has been added, so there is no way to get from one pass to the next.
+    NoFunctions,
+    /// The function at this index was asked for, by position or in a schedule, but there are only len of them.
+    NoFunction { index: usize, len: usize },
+    /// Weights that can't be drawn from: negative, or all 0, eg after removing the only function with any weight.
+    InvalidWeights(Vec<f64>),
+    /// A transition matrix with a different number of rows than there are functions.
+    Transitions { rows: usize, functions: usize },
+    /// A list of weights, or a row of transitions, that doesn't have one weight for each function.
+    WeightCount { weights: usize, functions: usize },
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeneratorError::NoFunctions => write!(
+                f,
+                "permutations must have at least one element! Add to it with Generator#add"
+            ),
+            GeneratorError::NoFunction { index, len } => {
+                write!(f, "there is no function {}, only {}", index, len)
+            }
+            GeneratorError::InvalidWeights(weights) => write!(f, "invalid weights {:?}", weights),
+            GeneratorError::Transitions { rows, functions } => write!(
+                f,
+                "the transition matrix has {} rows for {} functions",
+                rows, functions
+            ),
+            GeneratorError::WeightCount { weights, functions } => {
+                write!(f, "{} weights for {} functions", weights, functions)
+            }
+        }
+    }
+}
+
+impl Error for GeneratorError {}
+
+/// Ok if there is a function at index.
+pub(crate) fn check_index(index: usize, len: usize) -> Result<(), GeneratorError> {
+    match index < len {
+        true => Ok(()),
+        false => Err(GeneratorError::NoFunction { index, len }),
+    }
+}
+
+/// Ok if there is a weight for each of the functions.
+pub(crate) fn check_weight_count(weights: &[f64], functions: usize) -> Result<(), GeneratorError> {
+    match weights.len() == functions {
+        true => Ok(()),
+        false => Err(GeneratorError::WeightCount {
+            weights: weights.len(),
+            functions,
+        }),
+    }
+}
+
+/// What to draw from with the weights, or InvalidWeights if some are negative or they are all 0.
+pub(crate) fn weighted(weights: &[f64]) -> Result<WeightedIndex<f64>, GeneratorError> {
+    WeightedIndex::new(weights).map_err(|_| GeneratorError::InvalidWeights(weights.to_vec()))
+}
+
+/// Somewhere a trace can be streamed to one access at a time, so it never has to be held in memory as a whole.
+pub trait Sink<T> {
+    type Error;
+    fn push(&mut self, access: T) -> Result<(), Self::Error>;
+}
+
+impl<T> Sink<T> for Vec<T> {
+    type Error = Infallible;
+
+    fn push(&mut self, access: T) -> Result<(), Infallible> {
+        Vec::push(self, access);
+        Ok(())
+    }
+}
+
+/// Writes every access on its own line.
+pub struct Lines<W: io::Write>(pub W);
+
+impl<T: Display, W: io::Write> Sink<T> for Lines<W> {
+    type Error = io::Error;
+
+    fn push(&mut self, access: T) -> io::Result<()> {
+        writeln!(self.0, "{}", access)
+    }
+}
+
+/// The accesses of a generator one at a time, going through the passes lazily. Only the current pass is kept.
+/// Without a limit on the passes, an empty pass ends the accesses, since an unbounded generator with an empty start
+/// would otherwise be walked forever without giving any.
+pub struct Accesses<'a, T> {
+    passes: Box<dyn Iterator<Item = Vec<T>> + 'a>,
+    pass: std::vec::IntoIter<T>,
+    /// How many more passes can be started, None for no limit.
+    passes_left: Option<usize>,
+    /// How many more accesses can be given, None for no limit.
+    remaining: Option<usize>,
+}
+
+impl<'a, T> Accesses<'a, T> {
+    pub fn new(passes: Box<dyn Iterator<Item = Vec<T>> + 'a>) -> Self {
+        Accesses {
+            passes,
+            pass: Vec::new().into_iter(),
+            passes_left: None,
+            remaining: None,
+        }
+    }
+
+    /// Stops after this many passes, counting the start.
+    pub fn passes(mut self, passes: usize) -> Self {
+        self.passes_left = Some(passes);
+        self
+    }
+
+    /// Stops after this many accesses, even in the middle of a pass.
+    pub fn length(mut self, length: usize) -> Self {
+        self.remaining = Some(length);
+        self
+    }
+}
+
+impl<T> Iterator for Accesses<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        loop {
+            if let Some(access) = self.pass.next() {
+                self.remaining = self.remaining.map(|r| r - 1);
+                return Some(access);
+            }
+            if self.passes_left == Some(0) {
+                return None;
+            }
+            let pass = self.passes.next()?;
+            if pass.is_empty() && self.passes_left.is_none() {
+                return None;
+            }
+            self.pass = pass.into_iter();
+            self.passes_left = self.passes_left.map(|p| p - 1);
+        }
+    }
+}
+
 ///A generator is an interface that tells us that given a base set
 ///It will transform the set into another set as long as the operation is closed.
 ///Meaning that the set yielded is from same domain as the single operand.
 ///This generator is supposed to be as self contained as possible, so that if you want
 ///to add other ways of manipulating the trace outside of using a symmetric group.
+///The functions are Send + Sync and every generator is Clone, so one configured generator can be shared
+///between threads, or copied and changed with replace.
 pub trait Generator<'a, T>
 where
     T: PartialEq + Sized + Clone,
@@ -10,19 +169,117 @@ where
     fn start(&self) -> Vec<T>;
     fn set_start(&mut self, start: &[T]);
     /// This is supposed to manage adding some function to the generator.
-    fn add(&mut self, f: Box<dyn Fn(T) -> T>);
+    /// The function is applied to every element of the trace.
+    fn add(&mut self, f: Box<Evaluate<T>>)
+    where
+        T: 'static,
+    {
+        self.add_transform(elementwise(f));
+    }
+    /// Adds a function rewriting the whole trace at once.
+    fn add_transform(&mut self, f: Box<Transform<T>>);
+    /// Adds g acting on the trace through any group action, eg OnPositions to move elements around instead of renaming them.
+    fn add_action<G, A>(&mut self, action: A, g: G)
+    where
+        A: GroupAction<G, [T]> + Send + Sync + 'static,
+        G: Send + Sync + 'static,
+        T: 'static,
+        Self: Sized,
+    {
+        self.add_transform(transform(action, g));
+    }
+    /// Takes out the function at index, moving the ones after it down by one.
+    fn remove(&mut self, index: usize) -> Result<(), GeneratorError>;
+    /// Swaps the function at index for f, keeping its place (and anything else tied to it, like a weight).
+    fn replace(&mut self, index: usize, f: Box<Transform<T>>) -> Result<(), GeneratorError>;
+    /// How many functions have been added.
+    fn len(&self) -> usize;
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     /// This clears the list of permutations.
     fn clear(&mut self);
-    fn iter(&'a self) -> Box<dyn Iterator<Item = Vec<T>> + 'a>;
+    /// The passes, starting with the start, or why there can't be any.
+    fn try_iter(&'a self) -> Result<Box<dyn Iterator<Item = Vec<T>> + 'a>, GeneratorError>;
+    /// Like try_iter, but panics instead of returning an error.
+    fn iter(&'a self) -> Box<dyn Iterator<Item = Vec<T>> + 'a> {
+        self.try_iter().unwrap_or_else(|e| panic!("{}", e))
+    }
+    /// Every access of every pass, one at a time. Limit it with Accesses::passes or Accesses::length.
+    fn accesses(&'a self) -> Accesses<'a, T> {
+        Accesses::new(self.iter())
+    }
     /// This runs iter m times, then returns the total vector of the result.
     fn simulate(&'a self, m: usize) -> Vec<T> {
-        let mut iter = self.iter();
-        let mut out = Vec::new();
-        for _ in 0..=m {
-            if let Some(mut next_trace) = iter.next() {
-                out.append(&mut next_trace);
-            }
+        self.accesses().passes(m + 1).collect()
+    }
+    /// Like simulate, but hands every access to the sink as it is made instead of collecting them.
+    /// Returns how many there were, or the first error of the sink.
+    fn simulate_into<S>(&'a self, m: usize, sink: &mut S) -> Result<usize, S::Error>
+    where
+        S: Sink<T>,
+        Self: Sized,
+    {
+        let mut count = 0;
+        for access in self.accesses().passes(m + 1) {
+            sink.push(access)?;
+            count += 1;
         }
-        out
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::gen::{Generator, Lines};
+    use crate::generator::periodic::PeriodicGen;
+    use crate::group_theory::cycle::Cycle;
+    use crate::locality::reuse::{calculate_lru_hits, LruHits};
+
+    fn rotation() -> PeriodicGen<i32> {
+        let ground = vec![1, 2, 3];
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&ground);
+        generator.add(Cycle::from(vec![vec![1, 2, 3]], &ground).get_function());
+        generator
+    }
+
+    #[test]
+    fn limited_accesses() {
+        let generator = rotation();
+        let flat: Vec<i32> = generator.accesses().passes(3).collect();
+        debug_assert_eq!(flat, vec![1, 2, 3, 2, 3, 1, 3, 1, 2]);
+        debug_assert_eq!(flat, generator.simulate(2));
+        let cut: Vec<i32> = generator.accesses().length(5).collect();
+        debug_assert_eq!(cut, vec![1, 2, 3, 2, 3]);
+        debug_assert_eq!(generator.accesses().passes(2).length(100).count(), 6);
+        debug_assert_eq!(generator.accesses().passes(0).count(), 0);
+        // deep into the trace with only one pass held at a time
+        debug_assert_eq!(generator.accesses().nth(1 << 20), Some(3));
+    }
+
+    #[test]
+    fn empty_passes() {
+        let mut generator = rotation();
+        generator.set_start(&[]);
+        debug_assert_eq!(generator.accesses().count(), 0);
+        debug_assert_eq!(generator.accesses().length(5).count(), 0);
+        debug_assert_eq!(generator.accesses().passes(3).count(), 0);
+        debug_assert!(generator.simulate(3).is_empty());
+        debug_assert_eq!(generator.simulate_into(3, &mut Vec::new()), Ok(0));
+    }
+
+    #[test]
+    fn streaming_into_sinks() {
+        let generator = rotation();
+        let mut trace = Vec::new();
+        debug_assert_eq!(generator.simulate_into(3, &mut trace), Ok(12));
+        debug_assert_eq!(trace, generator.simulate(3));
+        let mut lines = Lines(Vec::new());
+        generator.simulate_into(1, &mut lines).unwrap();
+        debug_assert_eq!(String::from_utf8(lines.0).unwrap(), "1\n2\n3\n2\n3\n1\n");
+        let mut lru = LruHits::new(2);
+        generator.simulate_into(20, &mut lru).unwrap();
+        debug_assert_eq!(lru.hits(), calculate_lru_hits(&generator.simulate(20), 2));
     }
 }
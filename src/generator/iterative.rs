@@ -1 +1,439 @@
+use crate::generator::gen::{check_index, Generator, GeneratorError};
+use crate::group_theory::action::Transform;
+use std::borrow::Borrow;
+use std::hash::Hash;
+use std::sync::Arc;
 
+/// What each function of the schedule is applied to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Apply {
+    /// The pass before, so the passes are start, w_1(start), w_2(w_1(start)), ... like PeriodicGen.
+    #[default]
+    ToPrevious,
+    /// The start every time, so the passes are start, w_1(start), w_2(start), ...
+    ToStart,
+}
+
+/// One step of a schedule: the index of a function, or a sub-schedule run some number of times. Repeats are only
+/// walked as the iterator gets to them, so a schedule running [0, 1] a billion times stays small.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    Letter(usize),
+    Repeat(Vec<Schedule>, usize),
+}
+
+/// Drops the repeats that would walk nothing, so walking a schedule never spins without giving a letter.
+fn prune(schedule: &mut Vec<Schedule>) {
+    for step in schedule.iter_mut() {
+        if let Schedule::Repeat(body, _) = step {
+            prune(body);
+        }
+    }
+    schedule
+        .retain(|step| !matches!(step, Schedule::Repeat(body, k) if body.is_empty() || *k == 0));
+}
+
+/// Takes the letter out and moves the ones above it down by one.
+fn remove_letter(schedule: &mut Vec<Schedule>, index: usize) {
+    schedule.retain(|step| *step != Schedule::Letter(index));
+    for step in schedule.iter_mut() {
+        match step {
+            Schedule::Letter(letter) if *letter > index => *letter -= 1,
+            Schedule::Letter(_) => {}
+            Schedule::Repeat(body, _) => remove_letter(body, index),
+        }
+    }
+}
+
+fn check_letters(schedule: &[Schedule], len: usize) -> Result<(), GeneratorError> {
+    for step in schedule {
+        match step {
+            Schedule::Letter(letter) => check_index(*letter, len)?,
+            Schedule::Repeat(body, _) => check_letters(body, len)?,
+        }
+    }
+    Ok(())
+}
+
+/// The letters of a schedule one at a time, with a frame for every repeat the walk is inside. The schedule can be
+/// borrowed, or owned by the walk.
+pub struct Letters<S: Borrow<[Schedule]>> {
+    schedule: S,
+    /// How far into its steps each frame is and how many more times they run. The first frame walks the schedule
+    /// itself, and every other one the repeat just before where the frame under it is.
+    stack: Vec<(usize, usize)>,
+}
+
+impl<S: Borrow<[Schedule]>> Letters<S> {
+    /// Walks the schedule this many times. Empty repeats are skipped, but a repeat holding nothing but empty ones is
+    /// still gone through k times, which set_nested_schedule prunes away.
+    pub fn new(schedule: S, times: usize) -> Self {
+        let stack = match schedule.borrow().is_empty() || times == 0 {
+            true => Vec::new(),
+            false => vec![(0, times - 1)],
+        };
+        Letters { schedule, stack }
+    }
+
+    /// The steps the frame at this depth walks.
+    fn steps(&self, depth: usize) -> &[Schedule] {
+        let mut steps = self.schedule.borrow();
+        for &(position, _) in &self.stack[..depth] {
+            if let Schedule::Repeat(body, _) = &steps[position - 1] {
+                steps = body;
+            }
+        }
+        steps
+    }
+}
+
+enum Step {
+    Letter(usize),
+    Repeat(usize),
+}
+
+impl<S: Borrow<[Schedule]>> Iterator for Letters<S> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            let (position, left) = self.stack[depth];
+            // what the step is gets copied out first, so the steps are no longer borrowed when the stack changes
+            let step = match self.steps(depth).get(position) {
+                Some(Schedule::Letter(letter)) => Some(Step::Letter(*letter)),
+                Some(Schedule::Repeat(body, k)) if !body.is_empty() => Some(Step::Repeat(*k)),
+                Some(Schedule::Repeat(..)) => Some(Step::Repeat(0)),
+                None => None,
+            };
+            match step {
+                Some(Step::Letter(letter)) => {
+                    self.stack[depth].0 += 1;
+                    return Some(letter);
+                }
+                Some(Step::Repeat(k)) => {
+                    self.stack[depth].0 += 1;
+                    if k > 0 {
+                        self.stack.push((0, k - 1));
+                    }
+                }
+                None if left > 0 => self.stack[depth] = (0, left - 1),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// A generator that applies its functions in an explicit schedule, a word of function indices, instead of
+/// round-robin. With functions a and b the word [0, 0, 1, 0, 1, 1] gives the passes a a b a b b after the start.
+#[derive(Clone)]
+pub struct IterativeGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    start: Vec<T>,
+    functions: Vec<Arc<Transform<T>>>,
+    schedule: Vec<Schedule>,
+    /// How many times the schedule is run, None for forever.
+    repeats: Option<usize>,
+    /// Cuts the (repeated) schedule down to this many letters.
+    length: Option<usize>,
+    apply: Apply,
+}
+
+impl<T> Default for IterativeGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IterativeGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    pub fn new() -> Self {
+        IterativeGen {
+            start: Vec::new(),
+            functions: Vec::new(),
+            schedule: Vec::new(),
+            repeats: Some(1),
+            length: None,
+            apply: Apply::default(),
+        }
+    }
+
+    /// The word of function indices, counting the functions in the order they were added.
+    pub fn set_schedule(&mut self, schedule: &[usize]) {
+        self.schedule = schedule.iter().map(|&i| Schedule::Letter(i)).collect();
+    }
+
+    /// A schedule with repeats in it, ie [Letter(0), Repeat([Letter(1), Letter(0)], 3)] for a (b a)^3.
+    pub fn set_nested_schedule(&mut self, mut schedule: Vec<Schedule>) {
+        prune(&mut schedule);
+        self.schedule = schedule;
+    }
+
+    pub fn schedule(&self) -> &[Schedule] {
+        &self.schedule
+    }
+
+    /// Runs the schedule this many times, or forever with None. Once by default.
+    pub fn set_repeats(&mut self, repeats: Option<usize>) {
+        self.repeats = repeats;
+    }
+
+    /// Stops after this many letters of the repeated schedule, or doesn't with None.
+    pub fn set_length(&mut self, length: Option<usize>) {
+        self.length = length;
+    }
+
+    pub fn set_apply(&mut self, apply: Apply) {
+        self.apply = apply;
+    }
+
+    /// The letters the iterator goes through, after repeating and truncating.
+    pub fn letters(&self) -> Box<dyn Iterator<Item = usize> + '_> {
+        let repeated: Box<dyn Iterator<Item = usize>> = match self.repeats {
+            Some(k) => Box::new(Letters::new(&self.schedule[..], k)),
+            None if self.schedule.is_empty() => Box::new(std::iter::empty()),
+            None => {
+                Box::new(std::iter::repeat_with(|| Letters::new(&self.schedule[..], 1)).flatten())
+            }
+        };
+        match self.length {
+            Some(length) => Box::new(repeated.take(length)),
+            None => repeated,
+        }
+    }
+}
+
+impl<'a, T> Generator<'a, T> for IterativeGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    fn start(&self) -> Vec<T> {
+        self.start.clone()
+    }
+
+    fn set_start(&mut self, start: &[T]) {
+        self.start = start.to_vec();
+    }
+
+    fn add_transform(&mut self, f: Box<Transform<T>>) {
+        self.functions.push(Arc::from(f));
+    }
+
+    /// Takes the function out of the schedule too. The letters of the functions after it move down with them, so
+    /// every letter left still applies the function it did before.
+    fn remove(&mut self, index: usize) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.functions.remove(index);
+        remove_letter(&mut self.schedule, index);
+        prune(&mut self.schedule);
+        Ok(())
+    }
+
+    fn replace(&mut self, index: usize, f: Box<Transform<T>>) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.functions[index] = Arc::from(f);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Clears the schedule too, since its letters would point at whatever is added next.
+    fn clear(&mut self) {
+        self.functions.clear();
+        self.schedule.clear();
+    }
+
+    fn try_iter(&'a self) -> Result<Box<dyn Iterator<Item = Vec<T>> + 'a>, GeneratorError> {
+        if self.functions.is_empty() {
+            return Err(GeneratorError::NoFunctions);
+        }
+        check_letters(&self.schedule, self.len())?;
+        Ok(Box::new(IterativeGenIter {
+            curr: Some(self.start()),
+            letters: self.letters(),
+            generator: self,
+        }))
+    }
+}
+
+pub struct IterativeGenIter<'a, T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    /// The pass to give next, None once the schedule has run out.
+    curr: Option<Vec<T>>,
+    letters: Box<dyn Iterator<Item = usize> + 'a>,
+    generator: &'a IterativeGen<T>,
+}
+
+impl<T> Iterator for IterativeGenIter<'_, T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let old = self.curr.take()?;
+        self.curr = self.letters.next().map(|i| {
+            let f = &self.generator.functions[i];
+            match self.generator.apply {
+                Apply::ToPrevious => f(&old),
+                Apply::ToStart => f(&self.generator.start),
+            }
+        });
+        Some(old)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::gen::{Generator, GeneratorError};
+    use crate::generator::iterative::{Apply, IterativeGen, Letters, Schedule};
+    use crate::generator::periodic::PeriodicGen;
+    use crate::group_theory::cycle::Cycle;
+
+    fn rotate_and_swap() -> IterativeGen<i32> {
+        let ground = vec![1, 2, 3];
+        let mut generator = IterativeGen::new();
+        generator.set_start(&ground);
+        // a rotates, b swaps the first two
+        generator.add(Cycle::from(vec![vec![1, 2, 3]], ground.clone()).get_function());
+        generator.add(Cycle::from(vec![vec![1, 2]], ground).get_function());
+        generator
+    }
+
+    #[test]
+    fn schedule_a_a_b_a_b_b() {
+        let mut generator = rotate_and_swap();
+        generator.set_schedule(&[0, 0, 1, 0, 1, 1]);
+        let passes: Vec<Vec<i32>> = generator.iter().collect();
+        debug_assert_eq!(
+            passes,
+            vec![
+                vec![1, 2, 3],
+                vec![2, 3, 1],
+                vec![3, 1, 2],
+                vec![3, 2, 1],
+                vec![1, 3, 2],
+                vec![2, 3, 1],
+                vec![1, 3, 2],
+            ]
+        );
+        generator.set_apply(Apply::ToStart);
+        let passes: Vec<Vec<i32>> = generator.iter().take(4).collect();
+        debug_assert_eq!(
+            passes,
+            vec![vec![1, 2, 3], vec![2, 3, 1], vec![2, 3, 1], vec![2, 1, 3]]
+        );
+    }
+
+    #[test]
+    fn repeat_and_truncate() {
+        let mut generator = rotate_and_swap();
+        generator.set_schedule(&[1]);
+        generator.set_repeats(Some(2));
+        debug_assert_eq!(generator.simulate(5), vec![1, 2, 3, 2, 1, 3, 1, 2, 3]);
+        generator.set_schedule(&[0, 1]);
+        generator.set_repeats(None);
+        generator.set_length(Some(5));
+        debug_assert_eq!(generator.iter().count(), 6);
+        generator.set_repeats(Some(0));
+        debug_assert_eq!(generator.iter().count(), 1);
+    }
+
+    #[test]
+    fn nested_schedules() {
+        let mut generator = rotate_and_swap();
+        // a (b a)^2 b^0 (b)^1000000000000, walked lazily and cut short
+        generator.set_nested_schedule(vec![
+            Schedule::Letter(0),
+            Schedule::Repeat(vec![Schedule::Letter(1), Schedule::Letter(0)], 2),
+            Schedule::Repeat(vec![Schedule::Letter(1)], 0),
+            Schedule::Repeat(vec![Schedule::Letter(1)], 1_000_000_000_000),
+        ]);
+        debug_assert_eq!(generator.schedule().len(), 3);
+        debug_assert!(generator.letters().take(7).eq([0, 1, 0, 1, 0, 1, 1]));
+        let owned = vec![Schedule::Repeat(vec![Schedule::Letter(1)], 3)];
+        debug_assert!(Letters::new(owned, 2).eq([1; 6]));
+        let mut flat = rotate_and_swap();
+        flat.set_schedule(&[0, 1, 0, 1, 0, 1, 1]);
+        debug_assert_eq!(generator.simulate(7), flat.simulate(7));
+        // taking out the only function of a repeat takes out the repeat
+        generator.remove(1).unwrap();
+        debug_assert_eq!(
+            generator.schedule(),
+            &[
+                Schedule::Letter(0),
+                Schedule::Repeat(vec![Schedule::Letter(0)], 2)
+            ]
+        );
+        debug_assert_eq!(generator.iter().count(), 4);
+    }
+
+    #[test]
+    fn round_robin_is_periodic() {
+        let ground = vec![1, 2, 3];
+        let a = Cycle::from(vec![vec![1, 2, 3]], ground.clone());
+        let b = Cycle::from(vec![vec![1, 2]], ground.clone());
+        let mut periodic = PeriodicGen::new();
+        periodic.set_start(&ground);
+        periodic.add(a.get_function());
+        periodic.add(b.get_function());
+        let mut generator = rotate_and_swap();
+        generator.set_schedule(&[0, 1]);
+        generator.set_repeats(None);
+        debug_assert!(generator.iter().take(10).eq(periodic.iter().take(10)));
+    }
+
+    #[test]
+    fn removing_a_middle_function() {
+        let mut generator = rotate_and_swap();
+        generator.add(Box::new(|x| x));
+        generator.set_schedule(&[0, 2, 1, 2, 0]);
+        let mut expected = rotate_and_swap();
+        expected.set_schedule(&[0, 0]);
+        generator.remove(1).unwrap();
+        debug_assert_eq!(generator.schedule(), &[0, 1, 1, 0].map(Schedule::Letter));
+        debug_assert_eq!(generator.len(), 2);
+        // the swap is gone and the identity repeats the pass before, so every other pass is a rotation
+        debug_assert_eq!(
+            generator.iter().step_by(2).collect::<Vec<_>>(),
+            expected.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn clearing() {
+        let mut generator = rotate_and_swap();
+        generator.set_schedule(&[0, 1, 1]);
+        generator.clear();
+        debug_assert!(generator.schedule().is_empty());
+        debug_assert_eq!(
+            generator.try_iter().err(),
+            Some(GeneratorError::NoFunctions)
+        );
+        // the old schedule doesn't drive the new functions
+        generator.add(Box::new(|x: i32| x));
+        generator.set_start(&[1, 2, 3]);
+        debug_assert_eq!(generator.iter().count(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn schedule_past_the_functions() {
+        let mut generator = rotate_and_swap();
+        generator.set_schedule(&[0, 2]);
+        let _ = generator.iter();
+    }
+}
@@ -0,0 +1,528 @@
+use crate::generator::gen::{check_index, check_weight_count, weighted, Generator, GeneratorError};
+use crate::group_theory::action::Transform;
+use crate::locality::reuse::calculate_lru_hits;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// The distribution pi with pi P = pi for a transition matrix P, rows normalized to add up to 1. It's found by
+/// Gaussian elimination on pi (P - I) = 0 with the entries of pi adding up to 1. None when there is more than one,
+/// which happens when the chain can get stuck in either of two separate sets of states. Fails unless P is square
+/// and every row has no negative weights and not all 0, like MarkovGen::set_transitions.
+pub fn stationary_distribution(
+    transitions: &[Vec<f64>],
+) -> Result<Option<Vec<f64>>, GeneratorError> {
+    let k = transitions.len();
+    check_transitions(transitions, k)?;
+    let rows: Vec<Vec<f64>> = transitions.iter().map(|row| normalized(row)).collect();
+    // equation j is column j of P - I, and the last is swapped for the sum, with the right hand side at the end
+    let mut system: Vec<Vec<f64>> = (0..k)
+        .map(|j| {
+            let mut equation: Vec<f64> = (0..k)
+                .map(|i| rows[i][j] - if i == j { 1.0 } else { 0.0 })
+                .collect();
+            equation.push(0.0);
+            equation
+        })
+        .collect();
+    if let Some(last) = system.last_mut() {
+        *last = vec![1.0; k + 1];
+    }
+    for col in 0..k {
+        let pivot = (col..k)
+            .max_by(|&a, &b| system[a][col].abs().total_cmp(&system[b][col].abs()))
+            .unwrap_or(col);
+        if system[pivot][col].abs() < 1e-12 {
+            return Ok(None);
+        }
+        system.swap(col, pivot);
+        let pivot_row = system[col].clone();
+        for (r, equation) in system.iter_mut().enumerate() {
+            let factor = equation[col] / pivot_row[col];
+            if r != col && factor != 0.0 {
+                for (e, p) in equation.iter_mut().zip(pivot_row.iter()).skip(col) {
+                    *e -= factor * p;
+                }
+            }
+        }
+    }
+    Ok(Some(
+        system
+            .iter()
+            .enumerate()
+            .map(|(i, equation)| equation[k] / equation[i])
+            .collect(),
+    ))
+}
+
+/// Ok if there are k rows of k weights, each of which can be drawn from.
+fn check_transitions(transitions: &[Vec<f64>], k: usize) -> Result<(), GeneratorError> {
+    if transitions.len() != k {
+        return Err(GeneratorError::Transitions {
+            rows: transitions.len(),
+            functions: k,
+        });
+    }
+    for row in transitions {
+        check_weight_count(row, k)?;
+        weighted(row)?;
+    }
+    Ok(())
+}
+
+fn normalized(weights: &[f64]) -> Vec<f64> {
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|w| w / total).collect()
+}
+
+/// A generator whose state is the function it applies next. After each pass the next state is drawn from the row
+/// of the transition matrix for the current one, so phases of a program that switch between access orders with
+/// fixed probabilities can be modelled. The draws come from a ChaCha8 PRNG seeded with `seed`, so every call to
+/// iter gives the same trace, on any platform and with any version of rand.
+#[derive(Clone)]
+pub struct MarkovGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    start: Vec<T>,
+    permutations: Vec<Arc<Transform<T>>>,
+    /// Row i weighs the states that can follow i. None moves to any state with equal chance.
+    transitions: Option<Vec<Vec<f64>>>,
+    /// Weighs the state the first function is picked by, 1 each unless set.
+    initial: Vec<f64>,
+    seed: u64,
+}
+
+impl<T> Default for MarkovGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> MarkovGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    pub fn new(seed: u64) -> Self {
+        MarkovGen {
+            start: Vec::new(),
+            permutations: Vec::new(),
+            transitions: None,
+            initial: Vec::new(),
+            seed,
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Entry (i, j) weighs applying function j right after function i, counting the functions in the order they
+    /// were added. The rows don't need to add up to 1. Fails unless the matrix is square with a row for every
+    /// function, and every row has no negative weights and not all 0.
+    pub fn set_transitions(&mut self, transitions: &[Vec<f64>]) -> Result<(), GeneratorError> {
+        check_transitions(transitions, self.permutations.len())?;
+        self.transitions = Some(transitions.to_vec());
+        Ok(())
+    }
+
+    /// Weighs the state the first function is picked by, in the order the functions were added.
+    /// Fails unless there is one for every function, none negative and not all 0.
+    pub fn set_initial(&mut self, initial: &[f64]) -> Result<(), GeneratorError> {
+        check_weight_count(initial, self.permutations.len())?;
+        weighted(initial)?;
+        self.initial = initial.to_vec();
+        Ok(())
+    }
+
+    /// The transition matrix with its rows normalized, uniform unless set.
+    pub fn transitions(&self) -> Vec<Vec<f64>> {
+        let k = self.permutations.len();
+        match &self.transitions {
+            Some(transitions) => transitions.iter().map(|row| normalized(row)).collect(),
+            None => vec![vec![1.0 / k as f64; k]; k],
+        }
+    }
+
+    /// How often each function is applied in the long run, see stationary_distribution. Fails like try_iter.
+    pub fn stationary(&self) -> Result<Option<Vec<f64>>, GeneratorError> {
+        self.distributions()?;
+        stationary_distribution(&self.transitions())
+    }
+
+    /// The expected number of LRU hits in each of the first `passes` passes after the start, for a cache holding
+    /// cache_size elements. A pass is only looked at together with the one before, which gives its exact hits when
+    /// every pass is an arrangement of the same distinct elements, as with permutations of the ground set. The
+    /// chain is followed exactly rather than sampled, so this keeps every reachable pass around and is meant for
+    /// small ground sets. Fails like try_iter.
+    pub fn expected_hits(
+        &self,
+        cache_size: usize,
+        passes: usize,
+    ) -> Result<Vec<f64>, GeneratorError>
+    where
+        T: Debug,
+    {
+        self.distributions()?;
+        let transitions = self.transitions();
+        let initial = normalized(&self.initial);
+        // the chance of being at a pass and about to apply a given function
+        let mut states: HashMap<(Vec<T>, usize), f64> = initial
+            .iter()
+            .enumerate()
+            .filter(|(_, &p)| p > 0.0)
+            .map(|(i, &p)| ((self.start.clone(), i), p))
+            .collect();
+        let mut hits = Vec::with_capacity(passes);
+        for _ in 0..passes {
+            let mut expected = 0.0;
+            let mut next_states: HashMap<(Vec<T>, usize), f64> = HashMap::new();
+            for ((pass, i), p) in states {
+                let next = self.permutations[i](&pass);
+                let both = [pass.as_slice(), next.as_slice()].concat();
+                let pass_hits =
+                    calculate_lru_hits(&both, cache_size) - calculate_lru_hits(&pass, cache_size);
+                expected += p * pass_hits as f64;
+                for (j, &q) in transitions[i].iter().enumerate() {
+                    if q > 0.0 {
+                        *next_states.entry((next.clone(), j)).or_default() += p * q;
+                    }
+                }
+            }
+            hits.push(expected);
+            states = next_states;
+        }
+        Ok(hits)
+    }
+
+    /// What the first state and the state after each one are drawn from. Fails when there are no functions, when
+    /// functions were added after the transitions were set, or when removing one left a row with nothing to go to.
+    fn distributions(
+        &self,
+    ) -> Result<(WeightedIndex<f64>, Vec<WeightedIndex<f64>>), GeneratorError> {
+        if self.permutations.is_empty() {
+            return Err(GeneratorError::NoFunctions);
+        }
+        if let Some(transitions) = &self.transitions {
+            check_transitions(transitions, self.len())?;
+        }
+        let rows = self
+            .transitions()
+            .iter()
+            .map(|row| weighted(row))
+            .collect::<Result<_, _>>()?;
+        Ok((weighted(&self.initial)?, rows))
+    }
+}
+
+impl<'a, T> Generator<'a, T> for MarkovGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    fn start(&self) -> Vec<T> {
+        self.start.clone()
+    }
+
+    fn set_start(&mut self, start: &[T]) {
+        self.start = start.to_vec();
+    }
+
+    fn add_transform(&mut self, f: Box<Transform<T>>) {
+        self.permutations.push(Arc::from(f));
+        self.initial.push(1.0);
+    }
+
+    /// Removes the state too, its row and column of the transitions and its initial weight.
+    fn remove(&mut self, index: usize) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.permutations.remove(index);
+        self.initial.remove(index);
+        if let Some(transitions) = &mut self.transitions {
+            transitions.remove(index);
+            for row in transitions.iter_mut() {
+                row.remove(index);
+            }
+        }
+        Ok(())
+    }
+
+    /// The new function takes over the state of the old one.
+    fn replace(&mut self, index: usize, f: Box<Transform<T>>) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.permutations[index] = Arc::from(f);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.permutations.len()
+    }
+
+    fn clear(&mut self) {
+        self.permutations.clear();
+        self.transitions = None;
+        self.initial.clear();
+    }
+
+    fn try_iter(&'a self) -> Result<Box<dyn Iterator<Item = Vec<T>> + 'a>, GeneratorError> {
+        let (initial, rows) = self.distributions()?;
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let state = initial.sample(&mut rng);
+        Ok(Box::new(MarkovGenIter {
+            curr: self.start(),
+            state,
+            rows,
+            rng,
+            generator: self,
+        }))
+    }
+}
+
+pub struct MarkovGenIter<'a, T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    curr: Vec<T>,
+    /// The function to apply next.
+    state: usize,
+    rows: Vec<WeightedIndex<f64>>,
+    rng: ChaCha8Rng,
+    generator: &'a MarkovGen<T>,
+}
+
+impl<T> Iterator for MarkovGenIter<'_, T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_vec = self.generator.permutations[self.state](&self.curr);
+        self.state = self.rows[self.state].sample(&mut self.rng);
+        Some(std::mem::replace(&mut self.curr, next_vec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::gen::{Generator, GeneratorError};
+    use crate::generator::markov::{stationary_distribution, MarkovGen};
+    use crate::generator::periodic::PeriodicGen;
+    use crate::group_theory::cycle::Cycle;
+    use crate::locality::reuse::calculate_lru_hits;
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.len() == b.len() && a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-6)
+    }
+
+    #[test]
+    fn alternating_chain_is_periodic() {
+        let ground = vec![1, 2, 3, 4];
+        let a = Cycle::from(vec![vec![1, 2, 3, 4]], &ground);
+        let b = Cycle::from(vec![vec![1, 4], vec![2, 3]], &ground);
+        let mut generator = MarkovGen::new(3);
+        generator.set_start(&ground);
+        generator.add(a.get_function());
+        generator.add(b.get_function());
+        generator
+            .set_transitions(&[vec![0.0, 1.0], vec![1.0, 0.0]])
+            .unwrap();
+        generator.set_initial(&[1.0, 0.0]).unwrap();
+        let mut periodic = PeriodicGen::new();
+        periodic.set_start(&ground);
+        periodic.add(a.get_function());
+        periodic.add(b.get_function());
+        debug_assert!(generator.iter().take(10).eq(periodic.iter().take(10)));
+        debug_assert!(close(
+            &generator.stationary().unwrap().unwrap(),
+            &[0.5, 0.5]
+        ));
+    }
+
+    #[test]
+    fn phases_follow_the_transitions() {
+        // function i forgets the pass and writes i, so the trace after the start is the sequence of states
+        let mut generator = MarkovGen::new(11);
+        generator.set_start(&[0]);
+        for i in 0..3 {
+            generator.add(Box::new(move |_: usize| i));
+        }
+        let transitions = [
+            vec![0.0, 3.0, 1.0],
+            vec![1.0, 0.0, 0.0],
+            vec![1.0, 1.0, 2.0],
+        ];
+        generator.set_transitions(&transitions).unwrap();
+        let states: Vec<usize> = generator.accesses().skip(1).take(30_000).collect();
+        let mut counts = [[0.0; 3]; 3];
+        for step in states.windows(2) {
+            counts[step[0]][step[1]] += 1.0;
+        }
+        // every state moves on with the chances of its own row, which depend on where the chain is
+        for (row, expected) in counts.iter().zip(generator.transitions()) {
+            let total: f64 = row.iter().sum();
+            let seen: Vec<f64> = row.iter().map(|c| c / total).collect();
+            debug_assert!(seen
+                .iter()
+                .zip(&expected)
+                .all(|(s, e)| (s - e).abs() < 0.02));
+        }
+        debug_assert_eq!(counts[1][1] + counts[1][2] + counts[0][0], 0.0);
+        debug_assert_eq!(generator.simulate(50), generator.simulate(50));
+        let occupancy: Vec<f64> = (0..3)
+            .map(|i| states.iter().filter(|&&s| s == i).count() as f64 / states.len() as f64)
+            .collect();
+        debug_assert!(occupancy
+            .iter()
+            .zip(generator.stationary().unwrap().unwrap())
+            .all(|(o, p)| (o - p).abs() < 0.02));
+    }
+
+    #[test]
+    fn stationary_distributions() {
+        debug_assert!(close(
+            &stationary_distribution(&[
+                vec![0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0],
+                vec![1.0, 0.0, 0.0]
+            ])
+            .unwrap()
+            .unwrap(),
+            &[1.0 / 3.0; 3]
+        ));
+        debug_assert!(close(
+            &stationary_distribution(&[vec![9.0, 1.0], vec![1.0, 1.0]])
+                .unwrap()
+                .unwrap(),
+            &[5.0 / 6.0, 1.0 / 6.0]
+        ));
+        // 2 can't be reached, and 0 and 1 only pass to each other
+        debug_assert!(close(
+            &stationary_distribution(&[
+                vec![1.0, 3.0, 0.0],
+                vec![1.0, 0.0, 0.0],
+                vec![1.0, 1.0, 1.0]
+            ])
+            .unwrap()
+            .unwrap(),
+            &[4.0 / 7.0, 3.0 / 7.0, 0.0]
+        ));
+        // staying put forever has every distribution
+        debug_assert_eq!(
+            stationary_distribution(&[vec![1.0, 0.0], vec![0.0, 1.0]]),
+            Ok(None)
+        );
+        debug_assert_eq!(
+            stationary_distribution(&[vec![1.0, 1.0], vec![0.0, 0.0]]),
+            Err(GeneratorError::InvalidWeights(vec![0.0, 0.0]))
+        );
+        debug_assert_eq!(
+            stationary_distribution(&[vec![1.0, 1.0], vec![1.0]]),
+            Err(GeneratorError::WeightCount {
+                weights: 1,
+                functions: 2
+            })
+        );
+    }
+
+    #[test]
+    fn expected_hits_per_pass() {
+        // repeating the order never hits a cache of 2, reversing it hits twice
+        let ground = vec![1, 2, 3, 4];
+        let mut generator = MarkovGen::new(0);
+        generator.set_start(&ground);
+        generator.add(Cycle::identity(&ground).get_function());
+        generator.add(Cycle::from(vec![vec![1, 4], vec![2, 3]], &ground).get_function());
+        generator.set_initial(&[1.0, 0.0]).unwrap();
+        debug_assert!(close(
+            &generator.expected_hits(2, 3).unwrap(),
+            &[0.0, 1.0, 1.0]
+        ));
+        generator
+            .set_transitions(&[vec![0.0, 1.0], vec![0.0, 1.0]])
+            .unwrap();
+        debug_assert!(close(
+            &generator.expected_hits(2, 3).unwrap(),
+            &[0.0, 2.0, 2.0]
+        ));
+        // a chain with one path gives the hits of its trace
+        let total: f64 = generator.expected_hits(3, 4).unwrap().iter().sum();
+        let trace = generator.simulate(4);
+        debug_assert_eq!(total as usize, calculate_lru_hits(&trace, 3));
+    }
+
+    #[test]
+    fn removing_a_state() {
+        let mut generator = MarkovGen::new(5);
+        generator.set_start(&[0]);
+        generator.add(Box::new(|x: i64| x + 1));
+        generator.add(Box::new(|x: i64| x + 10));
+        generator.add(Box::new(|x: i64| x + 100));
+        generator
+            .set_transitions(&[
+                vec![0.0, 1.0, 0.0],
+                vec![0.0, 0.0, 1.0],
+                vec![1.0, 1.0, 0.0],
+            ])
+            .unwrap();
+        generator.remove(2).unwrap();
+        // 1 has nowhere left to go
+        debug_assert_eq!(
+            generator.try_iter().err(),
+            Some(GeneratorError::InvalidWeights(vec![0.0, 0.0]))
+        );
+        generator
+            .set_transitions(&[vec![0.0, 1.0], vec![1.0, 0.0]])
+            .unwrap();
+        generator.set_initial(&[1.0, 0.0]).unwrap();
+        debug_assert_eq!(generator.simulate(3), vec![0, 1, 11, 12]);
+        generator.add(Box::new(|x: i64| x));
+        debug_assert_eq!(
+            generator.try_iter().err(),
+            Some(GeneratorError::Transitions {
+                rows: 2,
+                functions: 3
+            })
+        );
+        debug_assert_eq!(
+            generator.expected_hits(1, 1).err(),
+            Some(GeneratorError::Transitions {
+                rows: 2,
+                functions: 3
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_transitions_and_initial_weights() {
+        let mut generator = MarkovGen::new(0);
+        generator.set_start(&[0]);
+        generator.add(Box::new(|x: i64| x));
+        debug_assert_eq!(
+            generator.set_transitions(&[vec![1.0], vec![1.0]]),
+            Err(GeneratorError::Transitions {
+                rows: 2,
+                functions: 1
+            })
+        );
+        debug_assert_eq!(
+            generator.set_initial(&[1.0, 1.0]),
+            Err(GeneratorError::WeightCount {
+                weights: 2,
+                functions: 1
+            })
+        );
+        debug_assert_eq!(
+            generator.set_initial(&[-1.0]),
+            Err(GeneratorError::InvalidWeights(vec![-1.0]))
+        );
+        // nothing was set, so it still runs
+        debug_assert_eq!(generator.simulate(2), vec![0, 0, 0]);
+    }
+}
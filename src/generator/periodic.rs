@@ -1,12 +1,15 @@
-use crate::generator::gen::Generator;
+use crate::generator::gen::{check_index, Generator, GeneratorError};
+use crate::group_theory::action::Transform;
 use std::hash::Hash;
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct PeriodicGen<T>
 where
     T: Clone + Hash + Eq + 'static,
 {
     start: Vec<T>,
-    permutations: Vec<Box<dyn Fn(T) -> T>>,
+    permutations: Vec<Arc<Transform<T>>>,
 }
 
 impl<T> Default for PeriodicGen<T>
@@ -42,20 +45,34 @@ where
         self.start = start.to_vec();
     }
 
-    fn add(&mut self, f: Box<dyn Fn(T) -> T>) {
-        self.permutations.push(f);
+    fn add_transform(&mut self, f: Box<Transform<T>>) {
+        self.permutations.push(Arc::from(f));
+    }
+
+    fn remove(&mut self, index: usize) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.permutations.remove(index);
+        Ok(())
+    }
+
+    fn replace(&mut self, index: usize, f: Box<Transform<T>>) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.permutations[index] = Arc::from(f);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.permutations.len()
     }
 
     fn clear(&mut self) {
         self.permutations.clear();
     }
 
-    fn iter(&'a self) -> Box<dyn Iterator<Item = Vec<T>> + 'a> {
+    fn try_iter(&'a self) -> Result<Box<dyn Iterator<Item = Vec<T>> + 'a>, GeneratorError> {
         match self.permutations.is_empty() {
-            false => Box::new(PeriodicGenIter::new(self)),
-            true => panic!(
-                "permutations must have at least one element! Add to it with PeriodicGen#add"
-            ),
+            false => Ok(Box::new(PeriodicGenIter::new(self))),
+            true => Err(GeneratorError::NoFunctions),
         }
     }
 }
@@ -91,11 +108,7 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         let funcs = &self.generator.permutations;
 
-        let next_vec = self
-            .curr
-            .iter()
-            .map(|x| funcs[self.index_state](x.clone()))
-            .collect();
+        let next_vec = funcs[self.index_state](&self.curr);
         self.index_state = (self.index_state + 1) % (funcs.len());
         let old = self.curr.clone();
         self.curr = next_vec;
@@ -107,7 +120,16 @@ where
 #[cfg(test)]
 mod tests {
 
-    use crate::{bimap, generator::gen::Generator, group_theory::cycle::Cycle};
+    use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+    use crate::{
+        bimap,
+        generator::gen::{Generator, GeneratorError},
+        group_theory::{
+            action::{elementwise, OnPositions},
+            cycle::Cycle,
+        },
+    };
 
     use super::PeriodicGen;
 
@@ -153,7 +175,7 @@ mod tests {
     fn trace_specific() {
         let ground = vec!["x_1", "x_2", "x_3", "x_4", "x_5"];
         let mut generator = PeriodicGen::new();
-        generator.set_start(&vec!["x_2"]);
+        generator.set_start(&["x_2"]);
         let cycle = Cycle::new(
             bimap!("x_2" => "x_3", "x_3" => "x_4", "x_4" => "x_2"),
             ground.clone(),
@@ -201,4 +223,87 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn trace_period_is_order() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let cycle = Cycle::from(vec![vec![1, 2, 3], vec![4, 5]], ground.clone());
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&ground);
+        generator.add(cycle.get_function());
+        let passes: Vec<Vec<i32>> = generator.iter().take(13).collect();
+        let period = cycle.order() as usize;
+        debug_assert_eq!(period, 6);
+        debug_assert!((1..period).all(|k| passes[k] != passes[0]));
+        debug_assert!((0..7).all(|k| passes[k] == passes[k + period]));
+    }
+
+    #[test]
+    fn trace_by_position() {
+        // moving the elements around instead of renaming them, from a trace that isn't the ground set
+        let ground = vec![1, 2, 3];
+        let cycle = Cycle::from(vec![vec![1, 2, 3]], ground);
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&["a", "b", "c"]);
+        generator.add_action(OnPositions, cycle);
+        debug_assert_eq!(
+            generator.simulate(2),
+            vec!["a", "b", "c", "c", "a", "b", "b", "c", "a"]
+        );
+    }
+
+    #[test]
+    fn try_iter_without_functions() {
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&[1, 2, 3]);
+        debug_assert_eq!(
+            generator.try_iter().err(),
+            Some(GeneratorError::NoFunctions)
+        );
+        generator.add(Box::new(|x| x + 1));
+        debug_assert!(generator.try_iter().is_ok());
+        debug_assert!(generator.remove(0).is_ok());
+        debug_assert!(generator.is_empty());
+        debug_assert!(generator.try_iter().is_err());
+    }
+
+    #[test]
+    fn remove_and_replace() {
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&[0]);
+        generator.add(Box::new(|x| x + 1));
+        generator.add(Box::new(|x| x + 10));
+        generator.add(Box::new(|x| x + 100));
+        debug_assert_eq!(generator.simulate(3), vec![0, 1, 11, 111]);
+        generator.remove(1).unwrap();
+        debug_assert_eq!(generator.simulate(3), vec![0, 1, 101, 102]);
+        generator
+            .replace(0, elementwise(Box::new(|x| x * 2)))
+            .unwrap();
+        debug_assert_eq!(generator.simulate(3), vec![0, 0, 100, 200]);
+        debug_assert_eq!(
+            generator.remove(2),
+            Err(GeneratorError::NoFunction { index: 2, len: 2 })
+        );
+        debug_assert!(generator.replace(5, elementwise(Box::new(|x| x))).is_err());
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let ground: Vec<i32> = (1..=5).collect();
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&ground);
+        generator.add(Cycle::from(vec![vec![1, 2, 3, 4, 5]], &ground).get_function());
+        let expected = generator.simulate(4);
+        let traces: Vec<Vec<i32>> = (0..8)
+            .into_par_iter()
+            .map(|_| generator.simulate(4))
+            .collect();
+        debug_assert!(traces.iter().all(|trace| trace == &expected));
+        // a copy can be changed without touching the original
+        let mut copy = generator.clone();
+        copy.replace(0, elementwise(Box::new(|x| x))).unwrap();
+        debug_assert_eq!(copy.simulate(1), vec![1, 2, 3, 4, 5, 1, 2, 3, 4, 5]);
+        debug_assert_eq!(generator.simulate(4), expected);
+    }
 }
@@ -0,0 +1,239 @@
+use crate::generator::gen::{check_index, check_weight_count, weighted, Generator, GeneratorError};
+use crate::group_theory::action::Transform;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// How RandomGen makes each pass from the one before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Draw {
+    /// Applies one of the added functions, picked according to the weights.
+    #[default]
+    Weighted,
+    /// Shuffles the pass uniformly at random, ignoring the added functions. When the start is the ground set this is
+    /// a fresh uniform random permutation of it every pass, the baseline with no structure at all.
+    Uniform,
+}
+
+/// A generator that picks its next function at random instead of round-robin. The choices come from a ChaCha8 PRNG
+/// seeded with `seed`, so every call to iter gives the same trace, on any platform and with any version of rand.
+#[derive(Clone)]
+pub struct RandomGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    start: Vec<T>,
+    permutations: Vec<Arc<Transform<T>>>,
+    /// One per function, 1 unless set.
+    weights: Vec<f64>,
+    seed: u64,
+    draw: Draw,
+}
+
+impl<T> Default for RandomGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> RandomGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    pub fn new(seed: u64) -> Self {
+        RandomGen {
+            start: Vec::new(),
+            permutations: Vec::new(),
+            weights: Vec::new(),
+            seed,
+            draw: Draw::default(),
+        }
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn set_draw(&mut self, draw: Draw) {
+        self.draw = draw;
+    }
+
+    /// How likely each function is to be picked, in the order they were added. They don't need to add up to 1.
+    /// Fails unless there is one for every function, none negative and not all 0.
+    pub fn set_weights(&mut self, weights: &[f64]) -> Result<(), GeneratorError> {
+        check_weight_count(weights, self.permutations.len())?;
+        weighted(weights)?;
+        self.weights = weights.to_vec();
+        Ok(())
+    }
+
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+}
+
+impl<'a, T> Generator<'a, T> for RandomGen<T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    fn start(&self) -> Vec<T> {
+        self.start.clone()
+    }
+
+    fn set_start(&mut self, start: &[T]) {
+        self.start = start.to_vec();
+    }
+
+    fn add_transform(&mut self, f: Box<Transform<T>>) {
+        self.permutations.push(Arc::from(f));
+        self.weights.push(1.0);
+    }
+
+    /// Removes the weight of the function too.
+    fn remove(&mut self, index: usize) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.permutations.remove(index);
+        self.weights.remove(index);
+        Ok(())
+    }
+
+    /// The new function keeps the weight of the old one.
+    fn replace(&mut self, index: usize, f: Box<Transform<T>>) -> Result<(), GeneratorError> {
+        check_index(index, self.len())?;
+        self.permutations[index] = Arc::from(f);
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.permutations.len()
+    }
+
+    fn clear(&mut self) {
+        self.permutations.clear();
+        self.weights.clear();
+    }
+
+    fn try_iter(&'a self) -> Result<Box<dyn Iterator<Item = Vec<T>> + 'a>, GeneratorError> {
+        let choices = match self.draw {
+            Draw::Weighted if self.permutations.is_empty() => {
+                return Err(GeneratorError::NoFunctions)
+            }
+            Draw::Weighted => Some(weighted(&self.weights)?),
+            Draw::Uniform => None,
+        };
+        Ok(Box::new(RandomGenIter {
+            curr: self.start(),
+            rng: ChaCha8Rng::seed_from_u64(self.seed),
+            choices,
+            generator: self,
+        }))
+    }
+}
+
+pub struct RandomGenIter<'a, T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    curr: Vec<T>,
+    rng: ChaCha8Rng,
+    /// None when drawing uniform permutations.
+    choices: Option<WeightedIndex<f64>>,
+    generator: &'a RandomGen<T>,
+}
+
+impl<T> Iterator for RandomGenIter<'_, T>
+where
+    T: Clone + Hash + Eq + 'static,
+{
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_vec = match &self.choices {
+            Some(choices) => {
+                let i = choices.sample(&mut self.rng);
+                self.generator.permutations[i](&self.curr)
+            }
+            None => {
+                let mut shuffled = self.curr.clone();
+                shuffled.shuffle(&mut self.rng);
+                shuffled
+            }
+        };
+        Some(std::mem::replace(&mut self.curr, next_vec))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::generator::gen::Generator;
+    use crate::generator::random::{Draw, RandomGen};
+    use crate::group_theory::cycle::Cycle;
+
+    #[test]
+    fn seeded_traces_repeat() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let make = |seed| {
+            let mut generator = RandomGen::new(seed);
+            generator.set_start(&ground);
+            generator.add(Cycle::from(vec![vec![1, 2, 3, 4, 5]], &ground).get_function());
+            generator.add(Cycle::from(vec![vec![1, 5], vec![2, 4]], &ground).get_function());
+            generator.add(Cycle::from(vec![vec![2, 3]], &ground).get_function());
+            generator
+        };
+        let a = make(42);
+        debug_assert_eq!(a.simulate(50), a.simulate(50));
+        debug_assert_eq!(a.simulate(50), make(42).simulate(50));
+        debug_assert_ne!(a.simulate(50), make(43).simulate(50));
+        debug_assert_eq!(a.iter().next(), Some(ground.clone()));
+    }
+
+    #[test]
+    fn weights_decide_the_choices() {
+        // counting picks: f adds 1 and g adds 1000000
+        let mut generator = RandomGen::new(7);
+        generator.set_start(&[0]);
+        generator.add(Box::new(|x: i64| x + 1));
+        generator.add(Box::new(|x: i64| x + 1_000_000));
+        generator.set_weights(&[3.0, 1.0]).unwrap();
+        let last = generator.iter().nth(4000).unwrap()[0];
+        let (f, g) = (last % 1_000_000, last / 1_000_000);
+        debug_assert_eq!(f + g, 4000);
+        debug_assert!((2800..3200).contains(&f));
+        generator.set_weights(&[1.0, 0.0]).unwrap();
+        debug_assert_eq!(generator.iter().nth(100), Some(vec![100]));
+    }
+
+    #[test]
+    fn weights_cannot_all_be_zero() {
+        let mut generator = RandomGen::new(0);
+        generator.add(Box::new(|x: i64| x));
+        debug_assert!(generator.set_weights(&[0.0]).is_err());
+        debug_assert!(generator.set_weights(&[1.0, 1.0]).is_err());
+        debug_assert_eq!(generator.weights(), &[1.0]);
+    }
+
+    #[test]
+    fn uniform_permutations() {
+        let ground = vec![1, 2, 3];
+        let mut generator = RandomGen::new(1);
+        generator.set_start(&ground);
+        generator.set_draw(Draw::Uniform);
+        let mut counts: HashMap<Vec<i32>, usize> = HashMap::new();
+        for pass in generator.iter().skip(1).take(6000) {
+            let mut sorted = pass.clone();
+            sorted.sort();
+            debug_assert_eq!(sorted, ground);
+            *counts.entry(pass).or_default() += 1;
+        }
+        debug_assert_eq!(counts.len(), 6);
+        debug_assert!(counts.values().all(|&c| (850..1150).contains(&c)));
+    }
+}
@@ -4,8 +4,8 @@ use petgraph::{
 };
 use std::collections::HashMap;
 
-//should this be &'a T ?
-pub type Evaluate<T> = dyn Fn(T) -> T;
+pub use crate::group_theory::action::Evaluate;
+use crate::group_theory::action::{elementwise, Transform};
 
 /// This is an action graph which consider of nodes that can be used to compute values.
 /// T is the actual functions/transformations stored, and V are the input to those functions in T.
@@ -14,7 +14,7 @@ where
     V: Clone,
 {
     backing_graph: Graph<String, String, Undirected>,
-    node_map: HashMap<NodeIndex, Box<Evaluate<V>>>,
+    node_map: HashMap<NodeIndex, Box<Transform<V>>>,
     //todo: it should probably be a general iterator, not Vec<V>
     value_map: HashMap<NodeIndex, Vec<V>>,
 }
@@ -40,7 +40,13 @@ where
         }
     }
 
+    /// A node applying func to every value.
     pub fn add_node(&mut self, func: Box<Evaluate<V>>, func_name: String) -> NodeIndex {
+        self.add_transform(elementwise(func), func_name)
+    }
+
+    /// A node rewriting the values as a whole, eg with group_theory::action::transform(OnPositions, g).
+    pub fn add_transform(&mut self, func: Box<Transform<V>>, func_name: String) -> NodeIndex {
         let ind = self.backing_graph.add_node(func_name);
         self.node_map.insert(ind, func);
         ind
@@ -66,7 +72,7 @@ where
         for node in self.backing_graph.node_indices() {
             let curr_val = self.value_map.get(&node).unwrap();
             let curr_func = self.node_map.get(&node).unwrap();
-            let new_values = curr_val.iter().map(|v| curr_func(v.clone())).collect();
+            let new_values = curr_func(curr_val);
             self.value_map.insert(node, new_values);
         }
     }
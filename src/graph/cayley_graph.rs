@@ -1,15 +1,14 @@
 use petgraph::graph::NodeIndex;
 
 use crate::graph::action_graph::ActionGraph;
+use crate::group_theory::action::{transform, GroupAction, OnValues};
 use crate::group_theory::cycle::Cycle;
 use crate::group_theory::group::Group;
-use crate::group_theory::symmetric::SymmetricGroup;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
 
-// NOTE THERE IS A SMALL DESIGN BUG !
-// We are suppose to take in Group ACTIONS not just regular groups.
+/// The Cayley graph of a group, where every node holds the start value acted on by its element.
 pub struct CayleyGraph<V>
 where
     V: Clone + Copy + Hash + Eq + 'static,
@@ -20,16 +19,30 @@ where
 
 impl<V> CayleyGraph<V>
 where
-    V: Debug + Copy + Eq + Hash + Clone + ToString + 'static,
+    V: Debug + Copy + Eq + Hash + Clone + ToString + Send + Sync + 'static,
 {
-    pub fn new(group: &SymmetricGroup<V>) -> Self {
+    /// Works for any group of cycles, ie SymmetricGroup, CyclicGroup, DihedralGroup or AlternatingGroup.
+    /// The elements rename the values, see with_action for anything else.
+    pub fn new<G>(group: &G) -> Self
+    where
+        G: Group<Cycle<V>>,
+    {
+        CayleyGraph::with_action(group, OnValues)
+    }
+
+    /// Every node acts on the start value with the given action, eg OnPositions to move the values around.
+    pub fn with_action<G, A>(group: &G, action: A) -> Self
+    where
+        G: Group<Cycle<V>>,
+        A: GroupAction<Cycle<V>, [V]> + Clone + Send + Sync + 'static,
+    {
         let mut ag = ActionGraph::<V>::new();
         //collect nodes first
         let mut nodemap: HashMap<Cycle<V>, NodeIndex> = HashMap::new();
         for g in group.get_set().into_iter() {
             nodemap.insert(
                 g.clone(),
-                ag.add_node(Box::new(g.get_function()), g.display()),
+                ag.add_transform(transform(action.clone(), g.clone()), g.display()),
             );
         }
         //
@@ -69,6 +82,12 @@ where
         }
     }
 
+    /// Starts every node from the same values and runs their actions once.
+    pub fn apply(&mut self, start: &[V]) {
+        self.action_graph.set_start_value(&start.to_vec());
+        self.action_graph.graph_apply();
+    }
+
     pub fn get_values(&self, cycle: Cycle<V>) -> Option<&Vec<V>> {
         let ind = self.node_map.get(&cycle).unwrap();
         self.action_graph.get_values(*ind)
@@ -81,13 +100,16 @@ mod tests {
     #[allow(unused_imports)]
     use crate::{
         graph::cayley_graph::CayleyGraph,
-        group_theory::{cycle::Cycle, symmetric::SymmetricGroup},
+        group_theory::{
+            action::OnPositions, cycle::Cycle, dihedral::dihedral, group::Group,
+            symmetric::SymmetricGroup,
+        },
     };
 
     #[test]
     fn group_action() {
         let ground: Vec<i32> = vec![1, 2, 3, 4, 5];
-        let group: SymmetricGroup<i32> = SymmetricGroup::new((&ground).len(), ground.clone());
+        let group: SymmetricGroup<i32> = SymmetricGroup::new(ground.len(), ground.clone());
 
         let mut s5_group: CayleyGraph<i32> = CayleyGraph::new(&group);
 
@@ -108,4 +130,32 @@ mod tests {
             Some(&vec![5, 4, 3, 2, 1])
         );
     }
+
+    #[test]
+    fn dihedral_action() {
+        let group = dihedral(4);
+        let mut graph: CayleyGraph<usize> = CayleyGraph::new(&group);
+        graph.action_graph.set_start_value(&vec![1, 2, 3, 4]);
+        graph.action_graph.graph_apply();
+        debug_assert_eq!(graph.node_map.len(), 8);
+        debug_assert_eq!(
+            graph.get_values(group.reflection(0)),
+            Some(&vec![1, 4, 3, 2])
+        );
+        debug_assert_eq!(graph.get_values(group.identity()), Some(&vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn position_action() {
+        let group: SymmetricGroup<i32> = SymmetricGroup::new(3, vec![1, 2, 3]);
+        let mut graph: CayleyGraph<i32> = CayleyGraph::with_action(&group, OnPositions);
+        graph.action_graph.set_start_value(&vec![7, 8, 9]);
+        graph.action_graph.graph_apply();
+        let g = group.create_vec(vec![vec![1, 2, 3]]);
+        debug_assert_eq!(graph.get_values(g), Some(&vec![9, 7, 8]));
+        debug_assert_eq!(
+            graph.get_values(group.create_vec(vec![vec![2, 3]])),
+            Some(&vec![7, 9, 8])
+        );
+    }
 }
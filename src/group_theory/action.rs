@@ -0,0 +1,172 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+
+/// A group acting on a set X: the identity does nothing and act(g * h, x) = act(g, act(h, x)).
+/// Keeping the action apart from the group lets the same group rewrite a trace in different ways.
+/// Sequences are acted on as slices, X = [V], and give back a Vec, so a trace is never copied just to be read.
+pub trait GroupAction<G, X: ToOwned + ?Sized> {
+    fn act(&self, g: &G, x: &X) -> X::Owned;
+}
+
+/// Any function of the right shape is an action, as long as it follows the rules above.
+impl<G, X, F> GroupAction<G, X> for F
+where
+    X: ToOwned + ?Sized,
+    F: Fn(&G, &X) -> X::Owned,
+{
+    fn act(&self, g: &G, x: &X) -> X::Owned {
+        self(g, x)
+    }
+}
+
+/// A permutation moving the elements of its ground set. Anything else is left alone, like Cycle::eval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OnPoints;
+
+impl<V> GroupAction<Cycle<V>, V> for OnPoints
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    fn act(&self, g: &Cycle<V>, x: &V) -> V {
+        g.eval(x.clone())
+    }
+}
+
+/// A permutation renaming every value of a sequence, which is what Cycle::get_function does to a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OnValues;
+
+impl<V> GroupAction<Cycle<V>, [V]> for OnValues
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    fn act(&self, g: &Cycle<V>, x: &[V]) -> Vec<V> {
+        x.iter().map(|v| g.eval(v.clone())).collect()
+    }
+}
+
+/// A permutation rearranging the positions of a sequence, reading position i as the i-th element of the ground set:
+/// whatever is at position i moves to the position of g(i). Positions past the ground set stay where they are.
+/// The sequence can hold anything. Panics if it is shorter than the ground set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OnPositions;
+
+impl<V, W> GroupAction<Cycle<V>, [W]> for OnPositions
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+    W: Clone,
+{
+    fn act(&self, g: &Cycle<V>, x: &[W]) -> Vec<W> {
+        assert!(
+            x.len() >= g.n(),
+            "a sequence of length {} has no room for a permutation of {} positions",
+            x.len(),
+            g.n()
+        );
+        let mut image = x.to_vec();
+        for (i, &j) in g.images().iter().enumerate() {
+            image[j as usize] = x[i].clone();
+        }
+        image
+    }
+}
+
+/// A permutation moving a set of elements, kept as a vector in ground order (anything outside the ground set last).
+/// Acting on every k-subset at once is what orbits of k-subsets are made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OnSubsets;
+
+impl<V> GroupAction<Cycle<V>, [V]> for OnSubsets
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    fn act(&self, g: &Cycle<V>, x: &[V]) -> Vec<V> {
+        let mut image = OnValues.act(g, x);
+        let ground = g.ground_set();
+        image.sort_by_key(|v| ground.index_of(v).unwrap_or(u32::MAX));
+        image
+    }
+}
+
+/// A function rewriting a whole trace, which is how ActionGraph and the generators store group elements.
+/// It can be shared between threads, so one generator can be evaluated in parallel.
+pub type Transform<V> = dyn Fn(&[V]) -> Vec<V> + Send + Sync;
+
+/// A function of a single element of a trace.
+pub type Evaluate<V> = dyn Fn(V) -> V + Send + Sync;
+
+/// Fixes the group element, turning an action on sequences into a Transform.
+pub fn transform<G, V, A>(action: A, g: G) -> Box<Transform<V>>
+where
+    A: GroupAction<G, [V]> + Send + Sync + 'static,
+    G: Send + Sync + 'static,
+    V: Clone + 'static,
+{
+    Box::new(move |trace: &[V]| action.act(&g, trace))
+}
+
+/// Applies a function to every element of a trace.
+pub fn elementwise<V>(f: Box<Evaluate<V>>) -> Box<Transform<V>>
+where
+    V: Clone + 'static,
+{
+    Box::new(move |trace: &[V]| trace.iter().map(|v| f(v.clone())).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::action::{
+        transform, GroupAction, OnPoints, OnPositions, OnSubsets, OnValues,
+    };
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::symmetric::sym;
+
+    #[test]
+    fn builtin_actions() {
+        let ground = vec![1, 2, 3, 4];
+        let g = Cycle::from(vec![vec![1, 2, 3]], &ground);
+        debug_assert_eq!(OnPoints.act(&g, &3), 1);
+        debug_assert_eq!(OnPoints.act(&g, &7), 7);
+        debug_assert_eq!(OnValues.act(&g, &[1, 1, 4, 3][..]), vec![2, 2, 4, 1]);
+        // the first entry moves to the second position, the second to the third and the third to the first
+        debug_assert_eq!(
+            OnPositions.act(&g, &[10, 20, 30, 40, 50][..]),
+            vec![30, 10, 20, 40, 50]
+        );
+        debug_assert_eq!(OnSubsets.act(&g, &[3, 4][..]), vec![1, 4]);
+        let t = transform(OnPositions, g);
+        debug_assert_eq!(t(&[1, 2, 3, 4]), vec![3, 1, 2, 4]);
+    }
+
+    #[test]
+    fn actions_respect_the_product() {
+        let s_4 = sym(4);
+        let x = [1, 2, 3, 4].as_slice();
+        let pair = [2, 4].as_slice();
+        let elements = s_4.get_set();
+        for g in elements.iter().step_by(5) {
+            for h in elements.iter().step_by(7) {
+                let gh = g * h;
+                debug_assert_eq!(OnValues.act(&gh, x), OnValues.act(g, &OnValues.act(h, x)));
+                debug_assert_eq!(
+                    OnPositions.act(&gh, x),
+                    OnPositions.act(g, &OnPositions.act(h, x))
+                );
+                debug_assert_eq!(
+                    OnSubsets.act(&gh, pair),
+                    OnSubsets.act(g, &OnSubsets.act(h, pair))
+                );
+            }
+        }
+        debug_assert_eq!(OnPositions.act(&s_4.identity(), x), x);
+    }
+
+    #[test]
+    fn closures_are_actions() {
+        let shift = |k: &i32, x: &i32| (x + k).rem_euclid(5);
+        debug_assert_eq!(shift.act(&3, &4), 2);
+    }
+}
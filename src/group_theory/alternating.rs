@@ -0,0 +1,155 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::enumeration::{EnumerationOrder, Permutations};
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::group::Group;
+use crate::math::combinations::checked_factorial;
+
+pub fn alt(n_size: usize) -> AlternatingGroup<usize> {
+    AlternatingGroup::new((1..=n_size).collect::<Vec<usize>>())
+}
+
+/// The even permutations of the ground set, which is half of S_n (for n >= 2).
+#[derive(Default)]
+pub struct AlternatingGroup<V>
+where
+    V: Hash + Eq,
+{
+    ground: GroundSet<V>,
+}
+
+impl<V> AlternatingGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + 'static,
+{
+    pub fn new(ground: impl Into<GroundSet<V>>) -> AlternatingGroup<V> {
+        AlternatingGroup {
+            ground: ground.into(),
+        }
+    }
+
+    pub fn get_ground(&self) -> Vec<V> {
+        self.ground.to_vec()
+    }
+
+    pub fn ground_set(&self) -> &GroundSet<V> {
+        &self.ground
+    }
+}
+
+impl<V> Group<Cycle<V>> for AlternatingGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + Send + Sync + 'static,
+{
+    /// The even permutations in lexicographic order.
+    fn elements(&self) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        Box::new(
+            Permutations::new(&self.ground, EnumerationOrder::Lexicographic)
+                .filter(|cycle| cycle.is_even()),
+        )
+    }
+
+    /// In lexicographic order ranks 2k and 2k + 1 only differ by swapping the last two elements, so exactly one of them
+    /// is even. That makes the k-th even permutation one of those two, and the listing can start there.
+    fn elements_range(
+        &self,
+        positions: Range<u128>,
+    ) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        let total = checked_factorial(self.ground.len()).unwrap_or(u128::MAX);
+        let end = positions.end.saturating_mul(2).min(total);
+        let start = positions.start.saturating_mul(2).min(end);
+        Box::new(
+            Permutations::range(&self.ground, EnumerationOrder::Lexicographic, start..end)
+                .filter(|cycle| cycle.is_even()),
+        )
+    }
+
+    fn seekable(&self) -> bool {
+        true
+    }
+
+    fn op(&self, a: Cycle<V>, b: Cycle<V>) -> Cycle<V> {
+        a * b
+    }
+
+    fn identity(&self) -> Cycle<V> {
+        Cycle::identity(&self.ground)
+    }
+
+    fn inverse(&self, e: Cycle<V>) -> Cycle<V> {
+        e.inverse()
+    }
+
+    /// n! / 2, and 1 for n < 2. Panics past n = 34, like SymmetricGroup.
+    fn order(&self) -> u128 {
+        let n = self.ground.len();
+        checked_factorial(n)
+            .map(|f| f.div_ceil(2))
+            .unwrap_or_else(|| panic!("the order of A_{} doesn't fit in a u128", n))
+    }
+
+    /// The 3-cycles (a_i, a_{i + 1}, a_{i + 2}) on consecutive elements, which generate all even permutations.
+    fn get_generator(&self) -> Vec<Cycle<V>> {
+        let n = self.ground.len();
+        (2..n)
+            .map(|i| {
+                let mut image: Vec<u32> = (0..n as u32).collect();
+                image[i - 2] = (i - 1) as u32;
+                image[i - 1] = i as u32;
+                image[i] = (i - 2) as u32;
+                Cycle::from_images(image, &self.ground)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::alternating::alt;
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::permutation_group::PermutationGroup;
+
+    #[test]
+    fn alternating_5() {
+        let group = alt(5);
+        let set = group.get_set();
+        debug_assert_eq!(set.len(), 60);
+        debug_assert_eq!(group.order(), 60);
+        debug_assert!(set.iter().all(|g| g.is_even()));
+        debug_assert!(!set.contains(&Cycle::from(vec![vec![1, 2]], group.ground_set())));
+        debug_assert_eq!(
+            group.get_generator()[0],
+            Cycle::from(vec![vec![1, 2, 3]], group.ground_set())
+        );
+    }
+
+    #[test]
+    fn generators_generate() {
+        for n in 1..=7 {
+            let group = alt(n);
+            let generated = PermutationGroup::new(group.get_generator(), group.ground_set());
+            debug_assert_eq!(generated.order(), group.order());
+        }
+        debug_assert_eq!(alt(4).get_set().len(), 12);
+    }
+
+    #[test]
+    fn elements_range() {
+        for n in 0..=5 {
+            let group = alt(n);
+            let all: Vec<Cycle<usize>> = group.elements().collect();
+            let order = group.order();
+            for (start, end) in [(0, order), (0, 1), (1, order), (order / 3, order / 2 + 1)] {
+                debug_assert_eq!(
+                    group.elements_range(start..end).collect::<Vec<_>>(),
+                    all[start as usize..end as usize]
+                );
+            }
+            debug_assert_eq!(group.elements_range(order..order + 5).count(), 0);
+        }
+    }
+}
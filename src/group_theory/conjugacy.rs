@@ -0,0 +1,212 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::enumeration::{EnumerationOrder, Permutations};
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::symmetric::SymmetricGroup;
+use crate::math::combinations::{checked_factorial, partitions};
+
+/// How many permutations commute with one of this cycle type: the product of k^m * m! over the cycle lengths k,
+/// where m is how many cycles have length k. None if it doesn't fit in a u128.
+pub fn centralizer_order(cycle_type: &[usize]) -> Option<u128> {
+    let mut lengths = cycle_type.to_vec();
+    lengths.sort_unstable();
+    lengths.chunk_by(|a, b| a == b).try_fold(1u128, |acc, run| {
+        let k = run[0] as u128;
+        let m = run.len();
+        let power = k.checked_pow(m as u32)?;
+        acc.checked_mul(power)?.checked_mul(checked_factorial(m)?)
+    })
+}
+
+/// How many permutations have this cycle type, n! divided by the centralizer order. None past n = 34.
+pub fn class_size(cycle_type: &[usize]) -> Option<u128> {
+    Some(checked_factorial(cycle_type.iter().sum())? / centralizer_order(cycle_type)?)
+}
+
+/// A conjugacy class of S_n. Two permutations are conjugate exactly when they have the same cycle type, so the
+/// classes are the partitions of n.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConjugacyClass<V>
+where
+    V: Clone + Hash + Eq + 'static,
+{
+    /// The cycle lengths from largest to smallest, as Cycle::cycle_type gives them.
+    pub cycle_type: Vec<usize>,
+    pub size: u128,
+    /// The canonical element, see Cycle::from_cycle_type.
+    pub representative: Cycle<V>,
+}
+
+impl<V> ConjugacyClass<V>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    /// The elements of the class, in lexicographic order. This walks through all of S_n.
+    pub fn elements(&self) -> impl Iterator<Item = Cycle<V>> + '_ {
+        Permutations::new(
+            self.representative.ground_set(),
+            EnumerationOrder::Lexicographic,
+        )
+        .filter(|w| w.cycle_type() == self.cycle_type)
+    }
+
+    pub fn contains(&self, w: &Cycle<V>) -> bool {
+        w.is_conjugate(&self.representative)
+    }
+}
+
+impl<T> Cycle<T>
+where
+    T: Debug + Clone + Eq + Hash + 'static,
+{
+    /// The canonical permutation of a cycle type: the cycles, from longest to shortest, run over consecutive elements
+    /// of the ground set, ie (1,2,3)(4,5) for [3, 2, 1] on 1..=6. Panics unless the lengths add up to the ground set.
+    pub fn from_cycle_type(cycle_type: &[usize], ground: impl Into<GroundSet<T>>) -> Self {
+        let ground = ground.into();
+        assert_eq!(
+            cycle_type.iter().sum::<usize>(),
+            ground.len(),
+            "the cycle type {:?} is not a partition of {}",
+            cycle_type,
+            ground.len()
+        );
+        let mut lengths = cycle_type.to_vec();
+        lengths.sort_unstable_by(|a, b| b.cmp(a));
+        let mut image = Vec::with_capacity(ground.len());
+        for len in lengths {
+            let start = image.len() as u32;
+            image.extend((1..len as u32).map(|i| start + i));
+            if len > 0 {
+                image.push(start);
+            }
+        }
+        Cycle::from_images(image, ground)
+    }
+
+    /// The canonical element of the conjugacy class of self.
+    pub fn class_representative(&self) -> Self {
+        Cycle::from_cycle_type(&self.cycle_type(), self.ground_set())
+    }
+
+    /// Whether other = t * self * t^-1 for some permutation t of the same ground set.
+    pub fn is_conjugate(&self, other: &Cycle<T>) -> bool {
+        self.ground_set() == other.ground_set() && self.cycle_type() == other.cycle_type()
+    }
+
+    /// A t with t * self * t^-1 = other, ie self.conjugate_by(&t) == other, found by lining up cycles of equal length.
+    /// None when they aren't conjugate.
+    pub fn conjugator(&self, other: &Cycle<T>) -> Option<Self> {
+        if !self.is_conjugate(other) {
+            return None;
+        }
+        let by_length = |w: &Cycle<T>| {
+            let mut cycles = w.get_index_cycles(true);
+            cycles.sort_by_key(|cycle| std::cmp::Reverse(cycle.len()));
+            cycles
+        };
+        let mut image = vec![0; self.n()];
+        for (a, b) in by_length(self).iter().zip(by_length(other).iter()) {
+            for (&i, &j) in a.iter().zip(b.iter()) {
+                image[i as usize] = j;
+            }
+        }
+        Some(Cycle::from_images(image, self.ground_set()))
+    }
+}
+
+impl<V> SymmetricGroup<V>
+where
+    V: Clone + Copy + Hash + Eq + PartialEq + Debug + 'static,
+{
+    /// The class of permutations with this cycle type. Panics if it isn't a partition of n, or past n = 34.
+    pub fn conjugacy_class(&self, cycle_type: &[usize]) -> ConjugacyClass<V> {
+        let representative = Cycle::from_cycle_type(cycle_type, self.ground_set());
+        let cycle_type = representative.cycle_type();
+        let size = class_size(&cycle_type)
+            .unwrap_or_else(|| panic!("S_{} is too large to count", self.ground_size()));
+        ConjugacyClass {
+            cycle_type,
+            size,
+            representative,
+        }
+    }
+
+    /// Every conjugacy class, one per partition of n, from the n-cycles down to the identity.
+    pub fn conjugacy_classes(&self) -> Vec<ConjugacyClass<V>> {
+        partitions(self.ground_size())
+            .iter()
+            .map(|cycle_type| self.conjugacy_class(cycle_type))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::group_theory::conjugacy::{centralizer_order, class_size};
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::symmetric::sym;
+
+    #[test]
+    fn classes_of_s5() {
+        let s_5 = sym(5);
+        let classes = s_5.conjugacy_classes();
+        // p(5) = 7
+        debug_assert_eq!(classes.len(), 7);
+        debug_assert_eq!(classes[0].cycle_type, vec![5]);
+        debug_assert_eq!(classes[6].cycle_type, vec![1, 1, 1, 1, 1]);
+        debug_assert_eq!(classes.iter().map(|c| c.size).sum::<u128>(), 120);
+        let mut counts: HashMap<Vec<usize>, u128> = HashMap::new();
+        for w in s_5.get_set() {
+            *counts.entry(w.cycle_type()).or_default() += 1;
+        }
+        for class in classes.iter() {
+            debug_assert_eq!(counts[&class.cycle_type], class.size);
+            debug_assert_eq!(class.representative.cycle_type(), class.cycle_type);
+            debug_assert_eq!(class.elements().count() as u128, class.size);
+        }
+        debug_assert_eq!(class_size(&[2, 2, 1]), Some(15));
+        debug_assert_eq!(centralizer_order(&[2, 2, 1]), Some(8));
+    }
+
+    #[test]
+    fn canonical_representatives() {
+        let ground: Vec<usize> = (1..=6).collect();
+        let w = Cycle::from(vec![vec![6, 2], vec![5, 1, 3]], &ground);
+        let r = w.class_representative();
+        debug_assert_eq!(r, Cycle::from(vec![vec![1, 2, 3], vec![4, 5]], &ground));
+        debug_assert_eq!(
+            Cycle::from_cycle_type(&[1, 2, 3], &ground),
+            Cycle::from_cycle_type(&[3, 2, 1], &ground)
+        );
+        debug_assert_eq!(
+            Cycle::from_cycle_type(&[1, 1, 1, 1, 1, 1], &ground),
+            Cycle::identity(&ground)
+        );
+    }
+
+    #[test]
+    fn conjugators() {
+        let s_5 = sym(5);
+        let elements = s_5.get_set();
+        for a in elements.iter().step_by(7) {
+            for b in elements.iter().step_by(11) {
+                match a.conjugator(b) {
+                    Some(t) => {
+                        debug_assert!(a.is_conjugate(b));
+                        debug_assert_eq!(&(&(&t * a) * &t.inverse()), b);
+                        debug_assert_eq!(&a.conjugate_by(&t), b);
+                    }
+                    None => debug_assert_ne!(a.cycle_type(), b.cycle_type()),
+                }
+            }
+        }
+        let a = s_5.create_vec(vec![vec![1, 2]]);
+        debug_assert!(!a.is_conjugate(&s_5.create_vec(vec![vec![1, 2, 3]])));
+        debug_assert!(!a.is_conjugate(&sym(6).create_vec(vec![vec![1, 2]])));
+    }
+}
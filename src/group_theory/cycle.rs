@@ -1,139 +1,315 @@
-use crate::bimap;
-
 use abstract_cache::ObjIdTraits;
 use bimap::BiMap;
+use serde::de::{self, DeserializeSeed};
 use serde::ser::SerializeMap;
-use serde::{Serialize, Serializer};
-use std::collections::HashSet;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::error::Error;
 use std::fmt::{self, Debug};
 use std::hash::Hash;
 use std::ops::Mul;
+use std::str::FromStr;
+
+use crate::group_theory::ground::GroundSet;
+
+/// Why a permutation could not be built. Elements are kept in their Debug form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermutationError {
+    /// The element is not in the ground set.
+    NotInGround(String),
+    /// More than one element is sent to this element.
+    NotBijective(String),
+    /// The element is listed more than once, ie in two cycles or twice in a retraversal.
+    Repeated(String),
+    /// The permutation doesn't cover the whole ground set.
+    LengthMismatch { expected: usize, found: usize },
+    /// A Lehmer code entry is too big for its position, or an image index is past the end of the ground set.
+    OutOfRange { position: usize, value: usize },
+}
+
+impl fmt::Display for PermutationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PermutationError::NotInGround(e) => write!(f, "{} is not in the ground set", e),
+            PermutationError::NotBijective(e) => {
+                write!(f, "{} is the image of more than one element", e)
+            }
+            PermutationError::Repeated(e) => write!(f, "{} appears more than once", e),
+            PermutationError::LengthMismatch { expected, found } => {
+                write!(f, "expected {} elements, found {}", expected, found)
+            }
+            PermutationError::OutOfRange { position, value } => {
+                write!(f, "{} is out of range at position {}", value, position)
+            }
+        }
+    }
+}
+
+impl Error for PermutationError {}
 
 ///For permutations
+///The permutation is stored as an image array over the indices of a shared ground set, so ground[i] maps to ground[image[i]].
 #[derive(Clone, Debug, Eq)]
 pub struct Cycle<T>
 where
     T: Clone + Hash + Eq + 'static,
 {
-    ground: Vec<T>,
-    ///this is the max cycle size
-    n: usize,
-    /// The main way to define a bimap
-    map: BiMap<T, T>,
-    //For hash
-    h: Vec<T>,
+    ground: GroundSet<T>,
+    /// image[i] is the index of where the i-th element of the ground set is sent
+    image: Vec<u32>,
 }
 
 impl<T> Cycle<T>
 where
     T: Debug + Clone + Eq + Hash + 'static,
 {
-    pub fn new(map: BiMap<T, T>, ground: Vec<T>) -> Self {
-        let mut new_map = map.clone();
-        let size = ground.len();
-        if map.len() != size {
+    pub fn new(map: BiMap<T, T>, ground: impl Into<GroundSet<T>>) -> Self {
+        Cycle::try_new(map, ground).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn from(vec: Vec<Vec<T>>, ground: impl Into<GroundSet<T>>) -> Self {
+        Cycle::try_from_cycles(vec, ground).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn from_retraversal(vec: &[T], ground: impl Into<GroundSet<T>>) -> Self {
+        Cycle::try_from_retraversal(vec, ground).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// The map sends each key to its value, and anything in the ground set that isn't a key is fixed.
+    /// Fails if the map uses elements outside of the ground set, or if two elements end up with the same image.
+    pub fn try_new(
+        map: BiMap<T, T>,
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, PermutationError> {
+        let ground = ground.into();
+        let index = |e: &T| {
             ground
-                .iter()
-                .filter(|g| !map.contains_left(g))
-                .for_each(|g: &T| {
-                    new_map.insert(g.clone(), g.clone());
-                });
+                .index_of(e)
+                .ok_or_else(|| PermutationError::NotInGround(format!("{:?}", e)))
+        };
+        for (from, to) in map.iter() {
+            index(from)?;
+            index(to)?;
         }
-        Cycle {
-            ground: ground.clone(),
-            n: size,
-            map: new_map.clone(),
-            h: ground
-                .into_iter()
-                .map(|x| new_map.get_by_left(&x).unwrap())
-                .cloned()
-                .collect(),
+        let image = ground
+            .iter()
+            .map(|g| index(map.get_by_left(g).unwrap_or(g)))
+            .collect::<Result<Vec<u32>, PermutationError>>()?;
+        let mut hit = vec![false; image.len()];
+        for &i in image.iter() {
+            if std::mem::replace(&mut hit[i as usize], true) {
+                return Err(PermutationError::NotBijective(format!("{:?}", ground[i])));
+            }
         }
+        Ok(Cycle { ground, image })
     }
 
-    pub fn from(vec: Vec<Vec<T>>, ground: Vec<T>) -> Self {
-        let mut set: HashSet<T> = HashSet::from_iter(ground.iter().cloned());
-        let mut map = BiMap::new();
-        for cycle in vec.into_iter() {
-            for window in cycle.windows(2) {
-                let prev = window[0].clone();
-                let curr = window[1].clone();
-
-                set.remove(&prev);
-                map.insert(prev, curr);
-            }
-            if let (Some(last), Some(first)) = (cycle.last(), cycle.first()) {
-                set.remove(last);
-                map.insert(last.clone(), first.clone());
+    /// Each inner vec is a cycle, ie vec![vec![1, 2, 3]] sends 1 to 2, 2 to 3 and 3 to 1.
+    /// Fails if an element is outside of the ground set, or shows up more than once across the cycles.
+    pub fn try_from_cycles(
+        vec: Vec<Vec<T>>,
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, PermutationError> {
+        let ground = ground.into();
+        let mut image: Vec<u32> = (0..ground.len() as u32).collect();
+        let mut seen = vec![false; ground.len()];
+        for cycle in vec.iter() {
+            let indices = cycle
+                .iter()
+                .map(|e| {
+                    let i = ground
+                        .index_of(e)
+                        .ok_or_else(|| PermutationError::NotInGround(format!("{:?}", e)))?;
+                    match std::mem::replace(&mut seen[i as usize], true) {
+                        true => Err(PermutationError::Repeated(format!("{:?}", e))),
+                        false => Ok(i),
+                    }
+                })
+                .collect::<Result<Vec<u32>, PermutationError>>()?;
+            for (k, &i) in indices.iter().enumerate() {
+                image[i as usize] = indices[(k + 1) % indices.len()];
             }
+        }
+        Ok(Cycle { ground, image })
+    }
+
+    /// The retraversal lists the image of every element of the ground set, in order.
+    /// Fails if it has the wrong length, uses elements outside of the ground set, or repeats an element.
+    pub fn try_from_retraversal(
+        vec: &[T],
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, PermutationError> {
+        let ground = ground.into();
+        if vec.len() != ground.len() {
+            return Err(PermutationError::LengthMismatch {
+                expected: ground.len(),
+                found: vec.len(),
+            });
+        }
+        let mut seen = vec![false; ground.len()];
+        let image = vec
+            .iter()
+            .map(|e| {
+                let i = ground
+                    .index_of(e)
+                    .ok_or_else(|| PermutationError::NotInGround(format!("{:?}", e)))?;
+                match std::mem::replace(&mut seen[i as usize], true) {
+                    true => Err(PermutationError::Repeated(format!("{:?}", e))),
+                    false => Ok(i),
+                }
+            })
+            .collect::<Result<Vec<u32>, PermutationError>>()?;
+        Ok(Cycle { ground, image })
+    }
 
-            // Handle unchanged elements:
-            for e in set.iter() {
-                map.insert(e.clone(), e.clone());
+    /// Builds a permutation from its image array, where image[i] is the index ground[i] is sent to.
+    /// Fails if it has the wrong length, an index past the end of the ground set, or two equal indices.
+    pub fn try_from_images(
+        image: Vec<u32>,
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, PermutationError> {
+        let ground = ground.into();
+        if image.len() != ground.len() {
+            return Err(PermutationError::LengthMismatch {
+                expected: ground.len(),
+                found: image.len(),
+            });
+        }
+        let mut hit = vec![false; image.len()];
+        for (position, &i) in image.iter().enumerate() {
+            match hit
+                .get_mut(i as usize)
+                .map(|hit| std::mem::replace(hit, true))
+            {
+                None => {
+                    return Err(PermutationError::OutOfRange {
+                        position,
+                        value: i as usize,
+                    })
+                }
+                Some(true) => {
+                    return Err(PermutationError::NotBijective(format!("{:?}", ground[i])))
+                }
+                Some(false) => {}
             }
         }
+        Ok(Cycle { ground, image })
+    }
 
+    /// Like try_from_images without the checks, for the groups that make elements in bulk from images they know
+    /// are permutations.
+    pub(crate) fn from_images(image: Vec<u32>, ground: impl Into<GroundSet<T>>) -> Self {
         Cycle {
-            ground: ground.clone(),
-            n: map.len(),
-            map: map.clone(),
-            h: ground
-                .into_iter()
-                .map(|x| map.get_by_left(&x).unwrap())
-                .cloned()
-                .collect(),
+            ground: ground.into(),
+            image,
         }
     }
 
-    pub fn from_retraversal(vec: &[T], ground: &Vec<T>) -> Self {
-        // Commenting out checks for big numbers
-        // assert_eq!(vec.len(), ground.len());
-        // assert_eq!(
-        //     vec.iter().collect::<HashSet<_>>(),
-        //     ground.iter().collect::<HashSet<_>>()
-        // );
-
-        Self::new(
-            vec.iter().zip(ground).fold(BiMap::new(), |mut map, ab| {
-                map.insert(ab.1.clone(), ab.0.clone());
-                map
-            }),
-            ground.clone(),
-        )
+    pub fn identity(ground: impl Into<GroundSet<T>>) -> Self {
+        let ground = ground.into();
+        Cycle {
+            image: (0..ground.len() as u32).collect(),
+            ground,
+        }
     }
 
     pub fn get_ground(&self) -> Vec<T> {
-        self.ground.clone()
+        self.ground.to_vec()
+    }
+
+    pub fn ground_set(&self) -> &GroundSet<T> {
+        &self.ground
+    }
+
+    pub fn images(&self) -> &[u32] {
+        &self.image
+    }
+
+    /// The size of the ground set the permutation acts on.
+    pub fn n(&self) -> usize {
+        self.image.len()
+    }
+
+    pub fn get_map(&self) -> BiMap<T, T> {
+        self.ground
+            .iter()
+            .zip(self.image.iter())
+            .map(|(g, &i)| (g.clone(), self.ground[i].clone()))
+            .collect()
     }
 
     pub fn inverse(&self) -> Self {
-        let mut co = BiMap::new();
-        for g in self.ground.clone() {
-            co.insert(g.clone(), self.map.get_by_left(&g).unwrap().clone());
+        let mut inv = vec![0; self.image.len()];
+        for (i, &j) in self.image.iter().enumerate() {
+            inv[j as usize] = i as u32;
+        }
+        Cycle {
+            ground: self.ground.clone(),
+            image: inv,
         }
-        Cycle::new(co, self.ground.clone())
+    }
+
+    /// self composed with itself k times. Negative powers are powers of the inverse.
+    /// Each cycle is just turned k steps, with k reduced mod its length, so this never needs the order.
+    pub fn pow(&self, k: i64) -> Self {
+        let mut image = self.image.clone();
+        for cycle in self.get_index_cycles(false) {
+            let len = cycle.len() as i64;
+            let k = k.rem_euclid(len) as usize;
+            for (i, &e) in cycle.iter().enumerate() {
+                image[e as usize] = cycle[(i + k) % cycle.len()];
+            }
+        }
+        Cycle {
+            ground: self.ground.clone(),
+            image,
+        }
+    }
+
+    /// other * self * other^-1, which relabels the cycles of self by other.
+    /// ie conjugating (1,2,3) by other gives (other(1),other(2),other(3)).
+    pub fn conjugate_by(&self, other: &Cycle<T>) -> Self {
+        &(other * self) * &other.inverse()
+    }
+
+    /// The commutator [self, other] = self^-1 * other^-1 * self * other, which is the identity exactly when they commute.
+    pub fn commutator(&self, other: &Cycle<T>) -> Self {
+        &(&self.inverse() * &other.inverse()) * &(self * other)
     }
 
     pub fn eval(&self, i: T) -> T {
-        match self.map.get_by_left(&i) {
-            Some(res) => res.clone(),
+        match self.ground.index_of(&i) {
+            Some(ind) => self.ground[self.image[ind as usize]].clone(),
             _ => i, // if it doesn't match, we will make an assumption here that it will just return the same thing
         }
     }
 
-    pub fn get_function(&self) -> Box<dyn Fn(T) -> T> {
-        let m = self.map.clone();
-        Box::new(move |e| m.get_by_left(&e).unwrap().clone())
+    /// Like eval, anything outside of the ground set is left alone.
+    pub fn get_function(&self) -> Box<dyn Fn(T) -> T + Send + Sync>
+    where
+        T: Send + Sync,
+    {
+        let ground = self.ground.clone();
+        let image = self.image.clone();
+        Box::new(move |e| match ground.index_of(&e) {
+            Some(i) => ground[image[i as usize]].clone(),
+            None => e,
+        })
+    }
+
+    /// The same permutation acting on the indices of the ground set instead of the elements.
+    /// This avoids hashing entirely, which is useful when only the shape of the trace matters (like LRU hits).
+    pub fn get_index_function(&self) -> Box<dyn Fn(u32) -> u32 + Send + Sync> {
+        let image = self.image.clone();
+        Box::new(move |i| image[i as usize])
     }
 
     pub fn get_retraversal_str(&self) -> String
     where
         T: ToString,
     {
-        self.ground
+        self.image
             .iter()
-            .map(|y| self.eval(y.clone()))
-            .map(|y| y.to_string())
+            .map(|&i| self.ground[i].to_string())
             .collect::<Vec<String>>()
             .join(",")
     }
@@ -143,15 +319,23 @@ where
     /// If show_one is True, then we should also show the full cycle representation (aka, include the 1-cycles)
     /// If show_ene is False, show the normal representation.
     pub fn get_cycle_representation(&self, show_one: bool) -> Vec<Vec<T>> {
-        let mut visited = HashSet::new();
+        self.get_index_cycles(show_one)
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(|i| self.ground[i].clone()).collect())
+            .collect()
+    }
+
+    /// Same as get_cycle_representation, but over the indices of the ground set.
+    pub fn get_index_cycles(&self, show_one: bool) -> Vec<Vec<u32>> {
+        let mut visited = vec![false; self.image.len()];
         let mut cycles = Vec::new();
-        for g in self.ground.clone() {
+        for g in 0..self.image.len() {
             let mut cycle = Vec::new();
-            let mut curr: &T = &g;
-            while !visited.contains(curr) {
-                visited.insert(curr.clone());
-                cycle.push(curr.clone());
-                curr = self.map.get_by_left(curr).unwrap();
+            let mut curr = g;
+            while !visited[curr] {
+                visited[curr] = true;
+                cycle.push(curr as u32);
+                curr = self.image[curr] as usize;
             }
 
             if !cycle.is_empty() && (cycle.len() != 1 || show_one) {
@@ -161,14 +345,13 @@ where
         cycles
     }
 
-    pub fn inversions(&self) -> usize
-    where
-        T: PartialOrd,
-    {
+    /// The number of pairs i < j with image[i] > image[j], where the order is the order of the ground set.
+    pub fn inversions(&self) -> usize {
+        let n = self.image.len();
         let mut inv = 0;
-        for i in (0..self.n).rev() {
-            for j in i + 1..self.n {
-                if self.h[i] > self.h[j] {
+        for i in (0..n).rev() {
+            for j in i + 1..n {
+                if self.image[i] > self.image[j] {
                     inv += 1;
                 }
             }
@@ -176,6 +359,43 @@ where
         inv
     }
 
+    /// The Lehmer code L, where L[i] counts the j > i with image[j] < image[i].
+    /// It sums to the number of inversions, and read as a factoradic number it is the lexicographic rank.
+    pub fn lehmer_code(&self) -> Vec<usize> {
+        let n = self.image.len();
+        (0..n)
+            .map(|i| {
+                (i + 1..n)
+                    .filter(|&j| self.image[j] < self.image[i])
+                    .count()
+            })
+            .collect()
+    }
+
+    /// Rebuilds a permutation from its Lehmer code: image[i] is the code[i]-th smallest index not used yet.
+    pub fn from_lehmer_code(
+        code: &[usize],
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, PermutationError> {
+        let ground = ground.into();
+        if code.len() != ground.len() {
+            return Err(PermutationError::LengthMismatch {
+                expected: ground.len(),
+                found: code.len(),
+            });
+        }
+        let mut unused: Vec<u32> = (0..ground.len() as u32).collect();
+        let image = code
+            .iter()
+            .enumerate()
+            .map(|(position, &value)| match value < unused.len() {
+                true => Ok(unused.remove(value)),
+                false => Err(PermutationError::OutOfRange { position, value }),
+            })
+            .collect::<Result<Vec<u32>, PermutationError>>()?;
+        Ok(Cycle { ground, image })
+    }
+
     pub fn display(&self) -> String
     where
         T: ToString,
@@ -207,46 +427,121 @@ where
     {
         // Start a struct serialization
         let mut state = serializer.serialize_map(Some(2))?;
-        state.serialize_entry("n", &self.n)?;
+        state.serialize_entry("n", &self.n())?;
         state.serialize_entry("value", &self.get_retraversal_str())?;
         state.end()
     }
 }
 
-impl<T> Mul for Cycle<T>
+/// The raw form written by Serialize, before it is checked against a ground set.
+#[derive(Deserialize)]
+struct CycleRepr {
+    n: usize,
+    value: String,
+}
+
+impl CycleRepr {
+    fn into_cycle<T>(self, ground: &GroundSet<T>) -> Result<Cycle<T>, String>
+    where
+        T: Debug + Clone + Eq + Hash + FromStr + 'static,
+    {
+        if self.n != ground.len() {
+            return Err(format!(
+                "expected a permutation of {} elements, but the ground set has {}",
+                self.n,
+                ground.len()
+            ));
+        }
+        let values = match self.value.is_empty() {
+            true => Vec::new(),
+            false => self
+                .value
+                .split(',')
+                .map(|v| {
+                    v.trim()
+                        .parse::<T>()
+                        .map_err(|_| format!("could not parse {:?}", v))
+                })
+                .collect::<Result<Vec<T>, String>>()?,
+        };
+        Cycle::try_from_retraversal(&values, ground).map_err(|e| e.to_string())
+    }
+}
+
+/// Reads back what Serialize writes, assuming the ground set is 1..=n (the one `sym(n)` uses).
+/// For any other ground set, use CycleSeed.
+impl<'de, T> Deserialize<'de> for Cycle<T>
+where
+    T: Debug + Clone + Eq + Hash + FromStr + TryFrom<usize> + 'static,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = CycleRepr::deserialize(deserializer)?;
+        let ground = (1..=repr.n)
+            .map(|i| {
+                T::try_from(i)
+                    .map_err(|_| de::Error::custom(format!("{} does not fit the element type", i)))
+            })
+            .collect::<Result<Vec<T>, D::Error>>()?;
+        repr.into_cycle(&GroundSet::new(ground))
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Deserializes a Cycle against a ground set supplied by the caller.
+/// The resulting cycles share the ground set, just like the ones made from a group.
+pub struct CycleSeed<'a, T>(pub &'a GroundSet<T>)
+where
+    T: Hash + Eq;
+
+impl<'de, T> DeserializeSeed<'de> for CycleSeed<'_, T>
+where
+    T: Debug + Clone + Eq + Hash + FromStr + 'static,
+{
+    type Value = Cycle<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        CycleRepr::deserialize(deserializer)?
+            .into_cycle(self.0)
+            .map_err(de::Error::custom)
+    }
+}
+
+impl<T> Mul for &Cycle<T>
 where
     T: Clone + Hash + Eq + Debug,
 {
     type Output = Cycle<T>;
 
-    fn mul(self, rhs: Cycle<T>) -> Self::Output {
-        if self.ground.len() != rhs.ground.len() {
+    /// (f * g)(x) = f(g(x))
+    fn mul(self, rhs: &Cycle<T>) -> Self::Output {
+        if self.ground != rhs.ground {
             panic!(
                 "The cycles being multiplied have the wrong ground sets! {:?} vs {:?}",
                 self.ground.len(),
                 rhs.ground.len()
             );
         }
-        let same_ground = self.ground.clone();
-
-        let mut new_map: BiMap<T, T> = BiMap::new();
-
-        for g in self.ground {
-            let pcyc = match rhs
-                .map
-                .get_by_left(&g)
-                .and_then(|f| self.map.get_by_left(f))
-            {
-                Some(res) => res.clone(),
-                None => panic!(
-                    "Compositions should not be empty! query: {:?} rhs: {:?}, lhs: {:?}",
-                    g, rhs.map, self.map
-                ),
-            };
-            new_map.insert(g, pcyc);
+        Cycle {
+            ground: self.ground.clone(),
+            image: rhs.image.iter().map(|&i| self.image[i as usize]).collect(),
         }
+    }
+}
 
-        Cycle::new(new_map, same_ground)
+impl<T> Mul for Cycle<T>
+where
+    T: Clone + Hash + Eq + Debug,
+{
+    type Output = Cycle<T>;
+
+    fn mul(self, rhs: Cycle<T>) -> Self::Output {
+        &self * &rhs
     }
 }
 
@@ -255,7 +550,7 @@ where
     T: Clone + Hash + Eq,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.map.eq(&other.map)
+        self.image == other.image && self.ground == other.ground
     }
 }
 
@@ -264,9 +559,7 @@ where
     T: Clone + Hash + Eq + Debug,
 {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        //self.ground.hash(state);
-        self.n.hash(state);
-        self.h.hash(state);
+        self.image.hash(state);
     }
 }
 impl<T> fmt::Display for Cycle<T>
@@ -280,12 +573,16 @@ where
 
 impl<V> ObjIdTraits for Cycle<V> where V: Clone + Hash + Eq + Debug + ToString {}
 
-/// Tests, mainly associative
+// Tests, mainly associative
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashSet;
 
+    use serde::de::DeserializeSeed;
+
+    use crate::group_theory::cycle::{CycleSeed, PermutationError};
+    use crate::group_theory::ground::GroundSet;
     use crate::group_theory::{cycle::Cycle, group::Group, symmetric::SymmetricGroup};
 
     #[test]
@@ -297,8 +594,14 @@ mod tests {
         //     3 1 5 4
         // g = 1 2 5 3 4
         //     2 5 1 4 3
-        debug_assert_eq!(f.map, crate::bimap![1 => 3, 3 => 1, 4 => 5, 5 => 4, 2 => 2]);
-        debug_assert_eq!(g.map, crate::bimap![1 => 2, 2 => 5, 5 => 1, 3 => 4, 4 => 3]);
+        debug_assert_eq!(
+            f.get_map(),
+            crate::bimap![1 => 3, 3 => 1, 4 => 5, 5 => 4, 2 => 2]
+        );
+        debug_assert_eq!(
+            g.get_map(),
+            crate::bimap![1 => 2, 2 => 5, 5 => 1, 3 => 4, 4 => 3]
+        );
     }
 
     #[test]
@@ -307,7 +610,10 @@ mod tests {
         let f = Cycle::from(vec![vec![1, 2]], ground.clone());
         // f = 1 3 4 5
         //     3 1 5 4
-        debug_assert_eq!(f.map, crate::bimap![1 => 2, 2 => 1, 3 => 3, 4 => 4, 5 => 5]);
+        debug_assert_eq!(
+            f.get_map(),
+            crate::bimap![1 => 2, 2 => 1, 3 => 3, 4 => 4, 5 => 5]
+        );
     }
 
     #[test]
@@ -348,6 +654,34 @@ mod tests {
         debug_assert_eq!(serialized, "{\n  \"n\": 5,\n  \"value\": \"5,4,3,2,1\"\n}");
     }
 
+    #[test]
+    fn deserialize1() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let f = Cycle::from(vec![vec![1, 5], vec![2, 4], vec![3]], ground.clone());
+        let serialized = serde_json::to_string(&f).unwrap();
+        let g: Cycle<i32> = serde_json::from_str(&serialized).unwrap();
+        debug_assert_eq!(f, g);
+    }
+
+    #[test]
+    fn deserialize_seed() {
+        let ground = GroundSet::new(vec!['a', 'b', 'c', 'd']);
+        let f = Cycle::from(vec![vec!['a', 'c', 'd']], &ground);
+        let serialized = serde_json::to_string(&f).unwrap();
+        let mut de = serde_json::Deserializer::from_str(&serialized);
+        let g = CycleSeed(&ground).deserialize(&mut de).unwrap();
+        debug_assert_eq!(f, g);
+        debug_assert!(g.ground_set().ptr_eq(&ground));
+    }
+
+    #[test]
+    fn deserialize_invalid() {
+        debug_assert!(serde_json::from_str::<Cycle<i32>>(r#"{"n": 3, "value": "1,2"}"#).is_err());
+        debug_assert!(serde_json::from_str::<Cycle<i32>>(r#"{"n": 3, "value": "1,1,2"}"#).is_err());
+        debug_assert!(serde_json::from_str::<Cycle<i32>>(r#"{"n": 3, "value": "1,2,4"}"#).is_err());
+        debug_assert!(serde_json::from_str::<Cycle<i32>>(r#"{"n": 3, "value": "1,x,3"}"#).is_err());
+    }
+
     #[test]
     fn construction6() {
         let ground = vec![1, 2, 3, 4, 5];
@@ -355,6 +689,72 @@ mod tests {
         debug_assert_eq!(&f.get_retraversal_str(), "1,2,3,4,5");
     }
 
+    #[test]
+    fn try_construction() {
+        let ground = vec![1, 2, 3, 4, 5];
+        debug_assert_eq!(
+            Cycle::try_from_cycles(vec![vec![1, 5], vec![2, 4]], ground.clone()),
+            Ok(Cycle::from(vec![vec![1, 5], vec![2, 4]], ground.clone()))
+        );
+        debug_assert_eq!(
+            Cycle::try_from_cycles(vec![vec![1, 5], vec![5, 4]], ground.clone()),
+            Err(PermutationError::Repeated(String::from("5")))
+        );
+        debug_assert_eq!(
+            Cycle::try_from_cycles(vec![vec![1, 6]], ground.clone()),
+            Err(PermutationError::NotInGround(String::from("6")))
+        );
+        debug_assert_eq!(
+            Cycle::try_new(crate::bimap![1 => 2], ground.clone()),
+            Err(PermutationError::NotBijective(String::from("2")))
+        );
+        debug_assert_eq!(
+            Cycle::try_new(crate::bimap![1 => 7, 7 => 1], ground.clone()),
+            Err(PermutationError::NotInGround(String::from("7")))
+        );
+        debug_assert_eq!(
+            Cycle::try_from_retraversal(&[5, 4, 3], ground.clone()),
+            Err(PermutationError::LengthMismatch {
+                expected: 5,
+                found: 3
+            })
+        );
+        debug_assert_eq!(
+            Cycle::try_from_retraversal(&[5, 4, 3, 4, 1], ground.clone()),
+            Err(PermutationError::Repeated(String::from("4")))
+        );
+        debug_assert_eq!(
+            Cycle::try_from_images(vec![4, 3, 2, 1, 0], ground.clone()),
+            Ok(Cycle::from(vec![vec![1, 5], vec![2, 4]], ground.clone()))
+        );
+        debug_assert_eq!(
+            Cycle::try_from_images(vec![0, 1, 2], ground.clone()),
+            Err(PermutationError::LengthMismatch {
+                expected: 5,
+                found: 3
+            })
+        );
+        debug_assert_eq!(
+            Cycle::try_from_images(vec![0, 1, 5, 3, 4], ground.clone()),
+            Err(PermutationError::OutOfRange {
+                position: 2,
+                value: 5
+            })
+        );
+        debug_assert_eq!(
+            Cycle::try_from_images(vec![0, 1, 1, 3, 4], ground.clone()),
+            Err(PermutationError::NotBijective(String::from("2")))
+        );
+    }
+
+    #[test]
+    fn fn_outside_ground() {
+        let ground = vec![1, 2, 3];
+        let func = Cycle::from(vec![vec![1, 2, 3]], ground).get_function();
+        debug_assert_eq!(func(1), 2);
+        debug_assert_eq!(func(10), 10);
+    }
+
     #[test]
     fn display1() {
         let ground = vec![1, 2, 3, 4, 5];
@@ -413,7 +813,7 @@ mod tests {
         debug_assert_eq!(f.clone(), g.clone());
         let mut set = HashSet::new();
         set.insert(f);
-        debug_assert_eq!(set.contains(&g), true);
+        debug_assert!(set.contains(&g));
     }
 
     #[test]
@@ -423,7 +823,39 @@ mod tests {
         let g = Cycle::from(vec![vec![1, 2, 5], vec![3, 4]], ground.clone());
         let fg: Cycle<_> = Cycle::from(vec![vec![1, 2, 4], vec![3, 5]], ground.clone());
 
-        debug_assert_eq!((f * g).map, fg.map);
+        debug_assert_eq!((f * g).get_map(), fg.get_map());
+    }
+
+    #[test]
+    fn pow() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let f = Cycle::from(vec![vec![1, 2, 3], vec![4, 5]], ground.clone());
+        let e = Cycle::identity(ground.clone());
+        debug_assert_eq!(f.order(), 6);
+        debug_assert_eq!(f.pow(0), e);
+        debug_assert_eq!(f.pow(1), f);
+        debug_assert_eq!(f.pow(2), f.clone() * f.clone());
+        debug_assert_eq!(f.pow(6), e);
+        debug_assert_eq!(f.pow(-1), f.inverse());
+        debug_assert_eq!(f.pow(-7), f.inverse());
+        debug_assert_eq!(f.pow(3), Cycle::from(vec![vec![4, 5]], ground.clone()));
+        debug_assert_eq!(f.pow(i64::MIN) * f.pow(i64::MAX), f.pow(-1));
+    }
+
+    #[test]
+    fn conjugate_commutator() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let f = Cycle::from(vec![vec![1, 2, 3]], ground.clone());
+        let g = Cycle::from(vec![vec![1, 5], vec![2, 4]], ground.clone());
+        let e = Cycle::identity(ground.clone());
+        debug_assert_eq!(
+            f.conjugate_by(&g),
+            Cycle::from(vec![vec![5, 4, 3]], ground.clone())
+        );
+        debug_assert_eq!(f.conjugate_by(&g).cycle_type(), f.cycle_type());
+        debug_assert_eq!(f.commutator(&f.pow(2)), e);
+        debug_assert_ne!(f.commutator(&g), e);
+        debug_assert_eq!(f.commutator(&g).inverse(), g.commutator(&f));
     }
 
     #[test]
@@ -0,0 +1,108 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::group::Group;
+
+pub fn cyclic(n_size: usize) -> CyclicGroup<usize> {
+    CyclicGroup::new((1..=n_size).collect::<Vec<usize>>())
+}
+
+/// The rotations of the ground set, read as a loop: a_1 -> a_2 -> ... -> a_n -> a_1.
+/// This is the group behind loop-rotation schedules, and it has order n.
+#[derive(Default)]
+pub struct CyclicGroup<V>
+where
+    V: Hash + Eq,
+{
+    ground: GroundSet<V>,
+}
+
+impl<V> CyclicGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + 'static,
+{
+    pub fn new(ground: impl Into<GroundSet<V>>) -> CyclicGroup<V> {
+        CyclicGroup {
+            ground: ground.into(),
+        }
+    }
+
+    pub fn get_ground(&self) -> Vec<V> {
+        self.ground.to_vec()
+    }
+
+    pub fn ground_set(&self) -> &GroundSet<V> {
+        &self.ground
+    }
+
+    /// The rotation that moves every element k steps forward around the loop.
+    pub fn rotation(&self, k: usize) -> Cycle<V> {
+        let n = self.ground.len();
+        Cycle::from_images((0..n).map(|i| ((i + k) % n) as u32).collect(), &self.ground)
+    }
+}
+
+impl<V> Group<Cycle<V>> for CyclicGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + Send + Sync + 'static,
+{
+    fn elements(&self) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        Box::new((0..self.ground.len().max(1)).map(|k| self.rotation(k)))
+    }
+
+    fn op(&self, a: Cycle<V>, b: Cycle<V>) -> Cycle<V> {
+        a * b
+    }
+
+    fn identity(&self) -> Cycle<V> {
+        Cycle::identity(&self.ground)
+    }
+
+    fn inverse(&self, e: Cycle<V>) -> Cycle<V> {
+        e.inverse()
+    }
+
+    fn order(&self) -> u128 {
+        self.ground.len().max(1) as u128
+    }
+
+    /// A single rotation by one step generates everything.
+    fn get_generator(&self) -> Vec<Cycle<V>> {
+        vec![self.rotation(1)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::gen::Generator;
+    use crate::generator::periodic::PeriodicGen;
+    use crate::group_theory::cyclic::cyclic;
+    use crate::group_theory::group::Group;
+
+    #[test]
+    fn cyclic_5() {
+        let group = cyclic(5);
+        let set = group.get_set();
+        debug_assert_eq!(set.len(), 5);
+        debug_assert_eq!(group.order(), 5);
+        let r = &group.get_generator()[0];
+        debug_assert_eq!(r.get_retraversal_str(), "2,3,4,5,1");
+        debug_assert_eq!(r.order(), 5);
+        debug_assert!(set.iter().all(|g| g.order() == 1 || g.order() == 5));
+        debug_assert_eq!(group.inverse(r.clone()) * r.clone(), group.identity());
+    }
+
+    #[test]
+    fn rotation_schedule() {
+        let group = cyclic(4);
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&group.get_ground());
+        generator.add(group.get_generator()[0].get_function());
+        let trace: Vec<Vec<usize>> = generator.iter().take(5).collect();
+        debug_assert_eq!(trace[1], vec![2, 3, 4, 1]);
+        // the period is the order of the group
+        debug_assert_eq!(trace[4], trace[0]);
+    }
+}
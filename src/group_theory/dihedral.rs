@@ -0,0 +1,129 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::group::Group;
+
+pub fn dihedral(n_size: usize) -> DihedralGroup<usize> {
+    DihedralGroup::new((1..=n_size).collect::<Vec<usize>>())
+}
+
+/// The symmetries of a ring whose vertices are the ground set, in order: the n rotations and the n reflections.
+/// Reflections reverse the direction of the ring, so this covers forward and backward sweeps.
+/// The order is 2n, except for n <= 2, where some of these coincide.
+#[derive(Default)]
+pub struct DihedralGroup<V>
+where
+    V: Hash + Eq,
+{
+    ground: GroundSet<V>,
+}
+
+impl<V> DihedralGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + 'static,
+{
+    pub fn new(ground: impl Into<GroundSet<V>>) -> DihedralGroup<V> {
+        DihedralGroup {
+            ground: ground.into(),
+        }
+    }
+
+    pub fn get_ground(&self) -> Vec<V> {
+        self.ground.to_vec()
+    }
+
+    pub fn ground_set(&self) -> &GroundSet<V> {
+        &self.ground
+    }
+
+    /// The rotation that moves every element k steps forward around the ring.
+    pub fn rotation(&self, k: usize) -> Cycle<V> {
+        let n = self.ground.len();
+        Cycle::from_images((0..n).map(|i| ((i + k) % n) as u32).collect(), &self.ground)
+    }
+
+    /// The reflection sending the i-th element to the (k - i)-th, counting around the ring from 0.
+    pub fn reflection(&self, k: usize) -> Cycle<V> {
+        let n = self.ground.len();
+        Cycle::from_images(
+            (0..n).map(|i| ((k + n - i) % n) as u32).collect(),
+            &self.ground,
+        )
+    }
+}
+
+impl<V> Group<Cycle<V>> for DihedralGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + Send + Sync + 'static,
+{
+    /// The rotations, then the reflections.
+    fn elements(&self) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        let n = self.ground.len();
+        let rotations = (0..n.max(1)).map(|k| self.rotation(k));
+        // for n <= 2 every reflection is also a rotation
+        let reflections = (0..n).filter(move |_| n > 2).map(|k| self.reflection(k));
+        Box::new(rotations.chain(reflections))
+    }
+
+    fn op(&self, a: Cycle<V>, b: Cycle<V>) -> Cycle<V> {
+        a * b
+    }
+
+    fn identity(&self) -> Cycle<V> {
+        Cycle::identity(&self.ground)
+    }
+
+    fn inverse(&self, e: Cycle<V>) -> Cycle<V> {
+        e.inverse()
+    }
+
+    fn order(&self) -> u128 {
+        match self.ground.len() {
+            0 | 1 => 1,
+            2 => 2,
+            n => 2 * n as u128,
+        }
+    }
+
+    /// The rotation by one step and the reflection fixing the first element.
+    fn get_generator(&self) -> Vec<Cycle<V>> {
+        vec![self.rotation(1), self.reflection(0)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::dihedral::dihedral;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::permutation_group::PermutationGroup;
+
+    #[test]
+    fn dihedral_orders() {
+        for n in 1..=7 {
+            let group = dihedral(n);
+            let set = group.get_set();
+            debug_assert_eq!(set.len() as u128, group.order());
+            debug_assert_eq!(group.elements().count() as u128, group.order());
+            let generated = PermutationGroup::new(group.get_generator(), group.ground_set());
+            debug_assert_eq!(generated.order(), group.order());
+            debug_assert!(set.iter().all(|g| generated.contains(g)));
+        }
+        debug_assert_eq!(dihedral(6).order(), 12);
+    }
+
+    #[test]
+    fn relations() {
+        let group = dihedral(5);
+        let (r, s) = (group.rotation(1), group.reflection(0));
+        // s r s = r^-1
+        debug_assert_eq!(&(&s * &r) * &s, r.inverse());
+        debug_assert_eq!(group.reflection(0).get_retraversal_str(), "1,5,4,3,2");
+        debug_assert_eq!(
+            group.reflection(1),
+            Cycle::from(vec![vec![1, 2], vec![3, 5]], group.ground_set())
+        );
+    }
+}
@@ -0,0 +1,315 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Range;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+use crate::math::combinations::{checked_factorial, next_permutation, to_factoradic};
+
+/// The orders S_n can be listed in. All of them can start at any position, which is what lets a listing be cut into
+/// independent chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EnumerationOrder {
+    /// Lexicographic order of one-line notation, the order of SymmetricGroup::rank.
+    #[default]
+    Lexicographic,
+    /// Heap's algorithm, where each permutation is one transposition away from the previous one.
+    Heap,
+    /// Steinhaus-Johnson-Trotter (plain changes), where each permutation is one adjacent transposition away from the
+    /// previous one.
+    SteinhausJohnsonTrotter,
+}
+
+/// The net effect of a whole run of Heap's algorithm on k elements: afterwards position p holds what was at effect[p].
+fn heap_effects(n: usize) -> Vec<Vec<u32>> {
+    let mut effects: Vec<Vec<u32>> = vec![Vec::new(), vec![0]];
+    for k in 2..=n {
+        let mut arr: Vec<u32> = (0..k as u32).collect();
+        for j in 0..k {
+            let inner = &effects[k - 1];
+            let moved: Vec<u32> = inner.iter().map(|&p| arr[p as usize]).collect();
+            arr[..k - 1].copy_from_slice(&moved);
+            if j < k - 1 {
+                arr.swap(if k % 2 == 0 { j } else { 0 }, k - 1);
+            }
+        }
+        effects.push(arr);
+    }
+    effects
+}
+
+/// The counters of iterative Heap's algorithm are the factoradic digits of the position, least significant first.
+fn heap_state(n: usize, k: u128) -> (Vec<u32>, Vec<usize>) {
+    let mut counters = to_factoradic(k, n).unwrap();
+    counters.reverse();
+    let effects = heap_effects(n);
+    let mut image: Vec<u32> = (0..n as u32).collect();
+    for i in (1..n).rev() {
+        // counters[i] full runs on the first i elements, each followed by a swap with the i-th
+        for j in 0..counters[i] {
+            let moved: Vec<u32> = effects[i].iter().map(|&p| image[p as usize]).collect();
+            image[..i].copy_from_slice(&moved);
+            image.swap(if i % 2 == 0 { 0 } else { j }, i);
+        }
+    }
+    (image, counters)
+}
+
+/// In plain changes the largest element sweeps over the listing of the others, right to left on their even positions
+/// and left to right on their odd ones. Also returns which way every element is moving (true for left).
+fn sjt_state(n: usize, k: u128) -> (Vec<u32>, Vec<bool>) {
+    let mut ranks = vec![k; n + 1];
+    for m in (1..n).rev() {
+        ranks[m] = ranks[m + 1] / (m as u128 + 1);
+    }
+    let mut image: Vec<u32> = Vec::with_capacity(n);
+    let mut left = vec![true; n];
+    for m in 1..=n {
+        let (q, r) = (ranks[m] / m as u128, (ranks[m] % m as u128) as usize);
+        left[m - 1] = q % 2 == 0;
+        let position = if left[m - 1] { m - 1 - r } else { r };
+        image.insert(position, (m - 1) as u32);
+    }
+    (image, left)
+}
+
+enum State {
+    Lexicographic(Vec<u32>),
+    Heap {
+        image: Vec<u32>,
+        counters: Vec<usize>,
+    },
+    SteinhausJohnsonTrotter {
+        image: Vec<u32>,
+        left: Vec<bool>,
+    },
+}
+
+impl State {
+    fn new(n: usize, order: EnumerationOrder, k: u128) -> State {
+        match order {
+            EnumerationOrder::Lexicographic => {
+                let code = to_factoradic(k, n).unwrap();
+                let mut unused: Vec<u32> = (0..n as u32).collect();
+                State::Lexicographic(code.iter().map(|&d| unused.remove(d)).collect())
+            }
+            EnumerationOrder::Heap => {
+                let (image, counters) = heap_state(n, k);
+                State::Heap { image, counters }
+            }
+            EnumerationOrder::SteinhausJohnsonTrotter => {
+                let (image, left) = sjt_state(n, k);
+                State::SteinhausJohnsonTrotter { image, left }
+            }
+        }
+    }
+
+    fn image(&self) -> &[u32] {
+        match self {
+            State::Lexicographic(image)
+            | State::Heap { image, .. }
+            | State::SteinhausJohnsonTrotter { image, .. } => image,
+        }
+    }
+
+    /// Moves to the next permutation. Only called when there is one.
+    fn advance(&mut self) {
+        match self {
+            State::Lexicographic(image) => {
+                next_permutation(image);
+            }
+            State::Heap { image, counters } => {
+                let i = (1..image.len()).find(|&i| counters[i] < i).unwrap();
+                image.swap(if i % 2 == 0 { 0 } else { counters[i] }, i);
+                counters[i] += 1;
+                counters[1..i].iter_mut().for_each(|c| *c = 0);
+            }
+            State::SteinhausJohnsonTrotter { image, left } => {
+                // move the largest mobile element, ie one looking at a smaller neighbour
+                let (p, q) = (0..image.len())
+                    .filter_map(|p| {
+                        let q = if left[image[p] as usize] {
+                            p.checked_sub(1)?
+                        } else {
+                            p + 1
+                        };
+                        (q < image.len() && image[q] < image[p]).then_some((p, q))
+                    })
+                    .max_by_key(|&(p, _)| image[p])
+                    .unwrap();
+                let v = image[p] as usize;
+                image.swap(p, q);
+                left[v + 1..].iter_mut().for_each(|l| *l = !*l);
+            }
+        }
+    }
+}
+
+/// A lazy listing of (part of) S_n, holding one permutation at a time.
+pub struct Permutations<V>
+where
+    V: Clone + Hash + Eq + 'static,
+{
+    ground: GroundSet<V>,
+    state: State,
+    remaining: u128,
+}
+
+impl<V> Permutations<V>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    /// All of S_n in the given order. Panics if n! doesn't fit in a u128.
+    pub fn new(ground: impl Into<GroundSet<V>>, order: EnumerationOrder) -> Permutations<V> {
+        let ground = ground.into();
+        let total = checked_factorial(ground.len())
+            .unwrap_or_else(|| panic!("S_{} is too large to list", ground.len()));
+        Permutations::range(ground, order, 0..total)
+    }
+
+    /// The permutations at the positions in the range, which is how a listing is split into chunks.
+    /// Starting somewhere in the middle costs O(n^2) (O(n^3) for Heap), after that every step is O(n).
+    /// Panics if the range goes past n!.
+    pub fn range(
+        ground: impl Into<GroundSet<V>>,
+        order: EnumerationOrder,
+        positions: Range<u128>,
+    ) -> Permutations<V> {
+        let ground = ground.into();
+        let n = ground.len();
+        assert!(
+            checked_factorial(n).is_some_and(|total| positions.end <= total),
+            "{:?} goes past the end of S_{}",
+            positions,
+            n
+        );
+        let remaining = positions.end.saturating_sub(positions.start);
+        let start = if remaining == 0 { 0 } else { positions.start };
+        Permutations {
+            state: State::new(n, order, start),
+            ground,
+            remaining,
+        }
+    }
+
+    /// Splits positions 0..n! into at most `chunks` contiguous, nonempty and independent pieces, in order.
+    pub fn chunks(
+        ground: impl Into<GroundSet<V>>,
+        order: EnumerationOrder,
+        chunks: usize,
+    ) -> Vec<Permutations<V>> {
+        let ground = ground.into();
+        let total = checked_factorial(ground.len())
+            .unwrap_or_else(|| panic!("S_{} is too large to list", ground.len()));
+        let size = total.div_ceil(chunks.max(1) as u128);
+        (0..chunks.max(1) as u128)
+            .map(|i| i * size..total.min((i + 1) * size))
+            .filter(|positions| !positions.is_empty())
+            .map(|positions| Permutations::range(&ground, order, positions))
+            .collect()
+    }
+}
+
+impl<V> Iterator for Permutations<V>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    type Item = Cycle<V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let cycle = Cycle::from_images(self.state.image().to_vec(), &self.ground);
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            self.state.advance();
+        }
+        Some(cycle)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match usize::try_from(self.remaining) {
+            Ok(remaining) => (remaining, Some(remaining)),
+            Err(_) => (usize::MAX, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::enumeration::{EnumerationOrder, Permutations};
+
+    const ORDERS: [EnumerationOrder; 3] = [
+        EnumerationOrder::Lexicographic,
+        EnumerationOrder::Heap,
+        EnumerationOrder::SteinhausJohnsonTrotter,
+    ];
+
+    /// The positions where two permutations differ.
+    fn difference(a: &Cycle<usize>, b: &Cycle<usize>) -> Vec<usize> {
+        (0..a.n())
+            .filter(|&i| a.images()[i] != b.images()[i])
+            .collect()
+    }
+
+    #[test]
+    fn every_permutation_once() {
+        let ground: Vec<usize> = (1..=6).collect();
+        for order in ORDERS {
+            let listing: Vec<Cycle<usize>> = Permutations::new(&ground, order).collect();
+            debug_assert_eq!(listing.len(), 720);
+            debug_assert_eq!(listing.iter().collect::<HashSet<_>>().len(), 720);
+            debug_assert_eq!(listing[0], Cycle::identity(&ground));
+        }
+    }
+
+    #[test]
+    fn gray_codes() {
+        let ground: Vec<usize> = (1..=5).collect();
+        let heap: Vec<Cycle<usize>> = Permutations::new(&ground, EnumerationOrder::Heap).collect();
+        debug_assert!(heap.windows(2).all(|w| difference(&w[0], &w[1]).len() == 2));
+        let sjt: Vec<Cycle<usize>> =
+            Permutations::new(&ground, EnumerationOrder::SteinhausJohnsonTrotter).collect();
+        debug_assert!(sjt.windows(2).all(|w| {
+            let diff = difference(&w[0], &w[1]);
+            diff.len() == 2 && diff[1] == diff[0] + 1
+        }));
+        // plain changes for n = 3: 123 132 312 321 231 213
+        let sjt_3: Vec<String> =
+            Permutations::new(vec![1, 2, 3], EnumerationOrder::SteinhausJohnsonTrotter)
+                .map(|c| c.get_retraversal_str())
+                .collect();
+        debug_assert_eq!(
+            sjt_3,
+            vec!["1,2,3", "1,3,2", "3,1,2", "3,2,1", "2,3,1", "2,1,3"]
+        );
+        let heap_3: Vec<String> = Permutations::new(vec![1, 2, 3], EnumerationOrder::Heap)
+            .map(|c| c.get_retraversal_str())
+            .collect();
+        debug_assert_eq!(
+            heap_3,
+            vec!["1,2,3", "2,1,3", "3,1,2", "1,3,2", "2,3,1", "3,2,1"]
+        );
+    }
+
+    #[test]
+    fn chunks_match_the_full_listing() {
+        let ground: Vec<usize> = (1..=6).collect();
+        for order in ORDERS {
+            let full: Vec<Cycle<usize>> = Permutations::new(&ground, order).collect();
+            for chunks in [1, 7, 24, 1000] {
+                let pieces = Permutations::chunks(&ground, order, chunks);
+                debug_assert!(pieces.len() <= chunks);
+                let joined: Vec<Cycle<usize>> = pieces.into_iter().flatten().collect();
+                debug_assert_eq!(joined, full);
+            }
+            let middle: Vec<Cycle<usize>> = Permutations::range(&ground, order, 100..107).collect();
+            debug_assert_eq!(middle, full[100..107].to_vec());
+            debug_assert_eq!(Permutations::range(&ground, order, 5..5).count(), 0);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+
+/// The generating sets SymmetricGroup can use. They decide the shape of the Cayley graph, and so which permutations
+/// are neighbours in chain_find. Below, 1, 2, ..., n stand for the elements of the ground set in order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GeneratingSet<V>
+where
+    V: Clone + Hash + Eq + 'static,
+{
+    /// The adjacent transpositions (i, i + 1). The length is the number of inversions.
+    #[default]
+    Adjacent,
+    /// The star transpositions (1, i). This is the star graph.
+    Star,
+    /// Every transposition (i, j). The length is n minus the number of cycles.
+    Transpositions,
+    /// The Coxeter pair {(1, 2), (1, 2, ..., n)}.
+    Coxeter,
+    /// The reversals of the first k elements, for k = 2..=n. This is the pancake graph.
+    PrefixReversals,
+    /// Any list of cycles over the ground set, which should generate all of S_n.
+    Custom(Vec<Cycle<V>>),
+}
+
+fn from_images<V>(ground: &GroundSet<V>, f: impl Fn(&mut Vec<u32>)) -> Cycle<V>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    let mut image: Vec<u32> = (0..ground.len() as u32).collect();
+    f(&mut image);
+    Cycle::from_images(image, ground)
+}
+
+impl<V> GeneratingSet<V>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    pub fn generators(&self, ground: &GroundSet<V>) -> Vec<Cycle<V>> {
+        let n = ground.len();
+        match self {
+            GeneratingSet::Adjacent => (1..n)
+                .map(|i| from_images(ground, |image| image.swap(i - 1, i)))
+                .collect(),
+            GeneratingSet::Star => (1..n)
+                .map(|i| from_images(ground, |image| image.swap(0, i)))
+                .collect(),
+            GeneratingSet::Transpositions => (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                .map(|(i, j)| from_images(ground, |image| image.swap(i, j)))
+                .collect(),
+            GeneratingSet::Coxeter if n < 2 => Vec::new(),
+            GeneratingSet::Coxeter => vec![
+                from_images(ground, |image| image.swap(0, 1)),
+                from_images(ground, |image| image.rotate_left(1)),
+            ],
+            GeneratingSet::PrefixReversals => (2..=n)
+                .map(|k| from_images(ground, |image| image[..k].reverse()))
+                .collect(),
+            GeneratingSet::Custom(generators) => generators.clone(),
+        }
+    }
+
+    /// The length of the shortest word for w in these generators, when there is a formula for it.
+    /// The star graph one is from Akers and Krishnamurthy: a cycle through 1 takes one swap per other element,
+    /// any other cycle takes two more than its length.
+    pub fn length(&self, w: &Cycle<V>) -> Option<usize> {
+        match self {
+            GeneratingSet::Adjacent => Some(w.inversions()),
+            GeneratingSet::Transpositions => Some(w.n() - w.num_cycles()),
+            GeneratingSet::Star => {
+                let moved = w.n() - w.fixed_points();
+                let cycles = w.get_index_cycles(false).len();
+                let first_moved = w.images().first().is_some_and(|&i| i != 0);
+                Some(moved + cycles - if first_moved { 2 } else { 0 })
+            }
+            _ => None,
+        }
+    }
+
+    /// The largest length, when there is a formula for it.
+    pub fn diameter(&self, n: usize) -> Option<usize> {
+        match self {
+            GeneratingSet::Adjacent => Some(n * n.saturating_sub(1) / 2),
+            GeneratingSet::Transpositions => Some(n.saturating_sub(1)),
+            GeneratingSet::Star => Some(3 * n.saturating_sub(1) / 2),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::generating::GeneratingSet;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::permutation_group::PermutationGroup;
+    use crate::group_theory::symmetric::sym;
+
+    fn sets() -> Vec<GeneratingSet<usize>> {
+        vec![
+            GeneratingSet::Adjacent,
+            GeneratingSet::Star,
+            GeneratingSet::Transpositions,
+            GeneratingSet::Coxeter,
+            GeneratingSet::PrefixReversals,
+        ]
+    }
+
+    #[test]
+    fn every_set_generates() {
+        for n in 1..=6 {
+            let s_n = sym(n);
+            for set in sets() {
+                let generators = set.generators(s_n.ground_set());
+                let generated = PermutationGroup::new(generators, s_n.ground_set());
+                debug_assert_eq!(generated.order(), s_n.order());
+            }
+        }
+    }
+
+    #[test]
+    fn lengths_match_distances() {
+        for set in sets() {
+            let s_5 = sym(5).with_generating_set(set.clone());
+            let distances = s_5.distances();
+            debug_assert_eq!(distances.len(), 120);
+            for (w, d) in distances.iter() {
+                if let Some(length) = set.length(w) {
+                    debug_assert_eq!(length, *d);
+                }
+            }
+            let furthest = distances.values().max().copied();
+            if let Some(diameter) = set.diameter(5) {
+                debug_assert_eq!(Some(diameter), furthest);
+            }
+        }
+        // the pancake numbers start 0, 1, 3, 4, 5, 7
+        let pancake = sym(6).with_generating_set(GeneratingSet::PrefixReversals);
+        debug_assert_eq!(pancake.distances().values().max(), Some(&7));
+    }
+}
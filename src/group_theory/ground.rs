@@ -0,0 +1,183 @@
+use indexmap::IndexSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Index;
+use std::sync::Arc;
+
+use crate::group_theory::cycle::PermutationError;
+
+/// The set a permutation acts on.
+/// Every element is interned once and given an index (its position in the set), so permutations only need to store
+/// where each index goes. Cloning a GroundSet is cheap, since clones share the same backing storage.
+#[derive(Clone, Debug)]
+pub struct GroundSet<T>
+where
+    T: Hash + Eq,
+{
+    elements: Arc<IndexSet<T>>,
+}
+
+impl<T> GroundSet<T>
+where
+    T: Clone + Hash + Eq,
+{
+    /// Panics if an element is listed more than once, see try_new.
+    pub fn new(elements: Vec<T>) -> Self {
+        let len = elements.len();
+        let ground = GroundSet {
+            elements: Arc::new(elements.into_iter().collect()),
+        };
+        assert_eq!(
+            ground.len(),
+            len,
+            "the elements of a ground set have to be distinct"
+        );
+        ground
+    }
+
+    /// Fails with Repeated if an element is listed more than once, which would shift the index of every element
+    /// after it.
+    pub fn try_new(elements: Vec<T>) -> Result<Self, PermutationError>
+    where
+        T: Debug,
+    {
+        let mut set = IndexSet::with_capacity(elements.len());
+        for e in elements {
+            if set.contains(&e) {
+                return Err(PermutationError::Repeated(format!("{:?}", e)));
+            }
+            set.insert(e);
+        }
+        Ok(GroundSet {
+            elements: Arc::new(set),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// The index of an element, if it is in the ground set.
+    pub fn index_of(&self, e: &T) -> Option<u32> {
+        self.elements.get_index_of(e).map(|i| i as u32)
+    }
+
+    pub fn contains(&self, e: &T) -> bool {
+        self.elements.contains(e)
+    }
+
+    pub fn get(&self, i: u32) -> Option<&T> {
+        self.elements.get_index(i as usize)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.elements.iter()
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.elements.iter().cloned().collect()
+    }
+
+    /// True if both ground sets share the same storage, which is the case for everything made from the same group.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.elements, &other.elements)
+    }
+}
+
+impl<T> Index<u32> for GroundSet<T>
+where
+    T: Hash + Eq,
+{
+    type Output = T;
+
+    fn index(&self, i: u32) -> &T {
+        &self.elements[i as usize]
+    }
+}
+
+impl<T> PartialEq for GroundSet<T>
+where
+    T: Hash + Eq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.elements, &other.elements)
+            || self.elements.iter().eq(other.elements.iter())
+    }
+}
+
+impl<T> Eq for GroundSet<T> where T: Hash + Eq {}
+
+impl<T> Default for GroundSet<T>
+where
+    T: Hash + Eq,
+{
+    fn default() -> Self {
+        GroundSet {
+            elements: Arc::new(IndexSet::new()),
+        }
+    }
+}
+
+impl<T> From<Vec<T>> for GroundSet<T>
+where
+    T: Clone + Hash + Eq,
+{
+    fn from(elements: Vec<T>) -> Self {
+        GroundSet::new(elements)
+    }
+}
+
+impl<T> From<&Vec<T>> for GroundSet<T>
+where
+    T: Clone + Hash + Eq,
+{
+    fn from(elements: &Vec<T>) -> Self {
+        GroundSet::new(elements.clone())
+    }
+}
+
+impl<T> From<&[T]> for GroundSet<T>
+where
+    T: Clone + Hash + Eq,
+{
+    fn from(elements: &[T]) -> Self {
+        GroundSet::new(elements.to_vec())
+    }
+}
+
+impl<T> From<&GroundSet<T>> for GroundSet<T>
+where
+    T: Clone + Hash + Eq,
+{
+    fn from(ground: &GroundSet<T>) -> Self {
+        ground.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::cycle::PermutationError;
+    use crate::group_theory::ground::GroundSet;
+
+    #[test]
+    fn distinct_elements() {
+        let ground = GroundSet::try_new(vec![3, 1, 2]).unwrap();
+        debug_assert_eq!(ground.len(), 3);
+        debug_assert_eq!(ground.index_of(&1), Some(1));
+        debug_assert_eq!(ground, GroundSet::new(vec![3, 1, 2]));
+        debug_assert_eq!(
+            GroundSet::try_new(vec![1, 1, 2]),
+            Err(PermutationError::Repeated(String::from("1")))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn repeated_elements() {
+        let _ = GroundSet::new(vec![1, 1, 2]);
+    }
+}
@@ -1,49 +1,65 @@
 use std::collections::{HashSet, VecDeque};
-use std::fmt::Debug;
 use std::hash::Hash;
-use std::ops::Mul;
+use std::ops::{Mul, Range};
+
+use rayon::iter::{ParallelBridge, ParallelIterator};
 
 /// A Group is a set system with some set G, the ground set and an arbitrary binary operator +.
 /// In particular it follows three properties:
 /// Associativity
 /// Identity
 /// Inverse
-pub trait Group<T: Hash + Eq + Sized + Clone> {
-    /// This gives a true search throughout the entire group set (hopefully finite).
-    /// This is done by using a BFS, starting at the identity of the element, and keep doing operations until you can't anymore.
+/// Groups are Sync and their elements Send, so the elements can be worked through on several threads.
+pub trait Group<T: Hash + Eq + Sized + Clone + Send + 'static>: Sync {
+    /// Every element of the group exactly once, produced lazily so large groups never have to be held in memory.
+    /// By default this is a BFS from the identity over the generators, which only remembers the elements it has seen.
     /// This means that for cyclic groups, this is the same as iter(get_generator()[0]), where there is only 1 generator.
-    fn get_set(&self) -> HashSet<T>
+    fn elements(&self) -> Box<dyn Iterator<Item = T> + Send + '_> {
+        Box::new(BfsElements {
+            group: self,
+            generators: self.get_generator(),
+            seen: HashSet::from([self.identity()]),
+            q: VecDeque::from([self.identity()]),
+        })
+    }
+
+    /// The elements at some positions of elements(), so a listing can be cut into pieces that are worked on separately.
+    /// By default this walks past the ones before the range, groups that can jump ahead do so.
+    fn elements_range(&self, positions: Range<u128>) -> Box<dyn Iterator<Item = T> + Send + '_> {
+        let skip = usize::try_from(positions.start).unwrap_or(usize::MAX);
+        let take =
+            usize::try_from(positions.end.saturating_sub(positions.start)).unwrap_or(usize::MAX);
+        Box::new(self.elements().skip(skip).take(take))
+    }
+
+    /// Whether elements_range jumps straight to its range. When it doesn't, a listing is better walked once with elements().
+    fn seekable(&self) -> bool {
+        false
+    }
+
+    /// The elements, spread over the rayon thread pool.
+    fn par_elements(&self) -> impl ParallelIterator<Item = T> + '_
     where
-        T: Debug,
+        Self: Sized,
     {
-        let generators: Vec<T> = self.get_generator();
-        let mut elements = HashSet::new();
-        //construct a bfs, with the identity element intiially in it.
-        let mut q = VecDeque::from([self.identity()]);
-        while !q.is_empty() {
-            if let Some(element) = q.pop_front() {
-                if elements.contains(&element) {
-                    continue;
-                }
-                elements.insert(element.clone());
-                for gen in generators.iter() {
-                    let new_element = self.op(gen.clone(), element.clone());
-                    q.push_front(new_element);
-                }
-            }
-        }
-        elements
+        self.elements().par_bridge()
+    }
+
+    /// This gives a true search throughout the entire group set (hopefully finite), all at once.
+    fn get_set(&self) -> HashSet<T> {
+        self.elements().collect()
     }
     fn op(&self, a: T, b: T) -> T;
 
     fn identity(&self) -> T;
     fn inverse(&self, e: T) -> T;
 
-    fn order(&self) -> i32;
+    /// The number of elements, exactly. Panics if it doesn't fit in a u128.
+    fn order(&self) -> u128;
     fn get_generator(&self) -> Vec<T>;
 
     /// This iterates one by one throughout the group set. For groups that aren't cyclic, this is not guaranteed to visit every single element.
-    fn iter(&self, start: T) -> GroupIter<T>
+    fn iter(&self, start: T) -> GroupIter<'_, T>
     where
         Self: Sized,
         T: Clone,
@@ -60,7 +76,7 @@ pub struct GroupIter<'a, T> {
 
 impl<'a, T> GroupIter<'a, T>
 where
-    T: Clone,
+    T: Hash + Eq + Clone + Send + 'static,
 {
     fn new(curr: T, g: &'a dyn Group<T>) -> GroupIter<'a, T> {
         GroupIter {
@@ -73,7 +89,7 @@ where
 
 impl<'a, T> Iterator for GroupIter<'a, T>
 where
-    T: Eq + Hash + PartialEq + Clone + Mul<&'a T> + Mul<T>,
+    T: Eq + Hash + PartialEq + Clone + Send + 'static + Mul<&'a T> + Mul<T>,
 {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
@@ -85,3 +101,81 @@ where
         }
     }
 }
+
+struct BfsElements<'a, G: ?Sized, T> {
+    group: &'a G,
+    generators: Vec<T>,
+    seen: HashSet<T>,
+    q: VecDeque<T>,
+}
+
+impl<G, T> Iterator for BfsElements<'_, G, T>
+where
+    G: Group<T> + ?Sized,
+    T: Hash + Eq + Clone + Send + 'static,
+{
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        let element = self.q.pop_front()?;
+        // marking elements as seen when they are queued keeps duplicates out of the queue
+        for gen in self.generators.iter() {
+            let new_element = self.group.op(gen.clone(), element.clone());
+            if self.seen.insert(new_element.clone()) {
+                self.q.push_back(new_element);
+            }
+        }
+        Some(element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rayon::iter::ParallelIterator;
+
+    use crate::group_theory::group::Group;
+
+    /// Z_n under addition, which only has the default elements().
+    struct Modular(i32);
+
+    impl Group<i32> for Modular {
+        fn op(&self, a: i32, b: i32) -> i32 {
+            (a + b) % self.0
+        }
+
+        fn identity(&self) -> i32 {
+            0
+        }
+
+        fn inverse(&self, e: i32) -> i32 {
+            (self.0 - e) % self.0
+        }
+
+        fn order(&self) -> u128 {
+            self.0 as u128
+        }
+
+        fn get_generator(&self) -> Vec<i32> {
+            vec![2, 3]
+        }
+    }
+
+    #[test]
+    fn bfs_elements() {
+        let group = Modular(12);
+        let elements: Vec<i32> = group.elements().collect();
+        debug_assert_eq!(elements.len(), 12);
+        debug_assert_eq!(elements[0], 0);
+        debug_assert_eq!(
+            elements.iter().collect::<HashSet<_>>().len(),
+            elements.len()
+        );
+        debug_assert_eq!(group.par_elements().count(), 12);
+        debug_assert_eq!(
+            group.elements_range(3..7).collect::<Vec<_>>(),
+            elements[3..7].to_vec()
+        );
+        debug_assert_eq!(group.get_set().len() as u128, group.order());
+    }
+}
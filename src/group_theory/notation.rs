@@ -0,0 +1,300 @@
+use std::error::Error;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+use std::str::FromStr;
+
+use crate::group_theory::cycle::{Cycle, PermutationError};
+use crate::group_theory::ground::GroundSet;
+
+/// Everything that can go wrong when reading a permutation written out as a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCycleError {
+    /// The string does not follow the notation, ie an unclosed "(1,2" or a missing second row.
+    Malformed { position: usize, reason: String },
+    /// The symbol, as written, could not be parsed into an element.
+    InvalidSymbol(String),
+    /// The elements parsed, but don't make a permutation of the ground set.
+    Invalid(PermutationError),
+}
+
+impl fmt::Display for ParseCycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCycleError::Malformed { position, reason } => {
+                write!(f, "malformed permutation at {}: {}", position, reason)
+            }
+            ParseCycleError::InvalidSymbol(s) => write!(f, "could not parse symbol {:?}", s),
+            ParseCycleError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ParseCycleError {}
+
+impl From<PermutationError> for ParseCycleError {
+    fn from(e: PermutationError) -> Self {
+        ParseCycleError::Invalid(e)
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == ',' || c.is_whitespace()
+}
+
+/// Splits a row like "5,4,3" or "5 4 3" into its symbols.
+fn symbols(row: &str) -> Vec<&str> {
+    row.split(is_separator).filter(|s| !s.is_empty()).collect()
+}
+
+/// Parses every symbol into an element. Whether they fit the ground set is up to the constructor they go to.
+fn resolve<T: FromStr>(row: &[&str]) -> Result<Vec<T>, ParseCycleError> {
+    row.iter()
+        .map(|&s| {
+            s.parse::<T>()
+                .map_err(|_| ParseCycleError::InvalidSymbol(s.to_string()))
+        })
+        .collect()
+}
+
+impl<T> Cycle<T>
+where
+    T: Debug + Clone + Eq + Hash + FromStr + 'static,
+{
+    /// Parses cycle notation, ie "(1,5)(2,4)", "(1 5)(2 4)" or "()" for the identity.
+    /// Elements may only appear once across all of the cycles.
+    pub fn parse_cycles(s: &str, ground: impl Into<GroundSet<T>>) -> Result<Self, ParseCycleError> {
+        let ground = ground.into();
+        let mut cycles: Vec<Vec<&str>> = Vec::new();
+        let mut open: Option<usize> = None;
+        for (i, c) in s.char_indices() {
+            match (c, open) {
+                ('(', None) => open = Some(i + 1),
+                ('(', Some(_)) => {
+                    return Err(ParseCycleError::Malformed {
+                        position: i,
+                        reason: String::from("cycles can't be nested"),
+                    })
+                }
+                (')', Some(start)) => {
+                    cycles.push(symbols(&s[start..i]));
+                    open = None;
+                }
+                (')', None) => {
+                    return Err(ParseCycleError::Malformed {
+                        position: i,
+                        reason: String::from("unmatched ')'"),
+                    })
+                }
+                (_, Some(_)) => {}
+                (c, None) if is_separator(c) => {}
+                (_, None) => {
+                    return Err(ParseCycleError::Malformed {
+                        position: i,
+                        reason: String::from("symbols must be inside a cycle"),
+                    })
+                }
+            }
+        }
+        if let Some(start) = open {
+            return Err(ParseCycleError::Malformed {
+                position: start - 1,
+                reason: String::from("unclosed '('"),
+            });
+        }
+        let cycles = cycles
+            .iter()
+            .map(|cycle| resolve(cycle))
+            .collect::<Result<Vec<Vec<T>>, _>>()?;
+        Ok(Cycle::try_from_cycles(cycles, ground)?)
+    }
+
+    /// Parses one-line notation, ie "5,4,3,2,1", the images of the ground set in order.
+    /// This is the same format get_retraversal_str writes.
+    pub fn parse_one_line(
+        s: &str,
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, ParseCycleError> {
+        let images = resolve(&symbols(s))?;
+        Ok(Cycle::try_from_retraversal(&images, ground)?)
+    }
+
+    /// Parses two-line notation, where the first row is sent to the second row.
+    /// The rows are split by a newline or a '/', ie "1 2 3 / 2 3 1". The first row can list the ground set in any order.
+    pub fn parse_two_line(
+        s: &str,
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, ParseCycleError> {
+        let ground = ground.into();
+        let rows: Vec<&str> = s
+            .split(['\n', '/'])
+            .filter(|r| !r.trim().is_empty())
+            .collect();
+        if rows.len() != 2 {
+            return Err(ParseCycleError::Malformed {
+                position: 0,
+                reason: format!("expected 2 rows, found {}", rows.len()),
+            });
+        }
+        // each row is the ground set sent somewhere, and top[i] goes to bottom[i]
+        let top = Cycle::try_from_retraversal(&resolve(&symbols(rows[0]))?, &ground)?;
+        let bottom = Cycle::try_from_retraversal(&resolve(&symbols(rows[1]))?, &ground)?;
+        Ok(&bottom * &top.inverse())
+    }
+
+    /// Picks the notation from the shape of the string: cycle notation if it starts with '(',
+    /// two-line notation if it has two rows, and one-line notation otherwise.
+    pub fn parse(s: &str, ground: impl Into<GroundSet<T>>) -> Result<Self, ParseCycleError> {
+        let trimmed = s.trim();
+        if trimmed.starts_with('(') {
+            Cycle::parse_cycles(trimmed, ground)
+        } else if trimmed.contains(['\n', '/']) {
+            Cycle::parse_two_line(trimmed, ground)
+        } else {
+            Cycle::parse_one_line(trimmed, ground)
+        }
+    }
+}
+
+/// Parses with the ground set 1..=n, the one `sym(n)` uses.
+/// For one-line and two-line notation n is the length of a row, and for cycle notation it is the largest element written.
+impl<T> FromStr for Cycle<T>
+where
+    T: Debug + Clone + Eq + Hash + FromStr + TryFrom<usize> + TryInto<usize> + 'static,
+{
+    type Err = ParseCycleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let n = if trimmed.starts_with('(') {
+            trimmed
+                .split(|c| is_separator(c) || c == '(' || c == ')')
+                .filter(|e| !e.is_empty())
+                .map(|e| {
+                    e.parse::<T>()
+                        .ok()
+                        .and_then(|t| t.try_into().ok())
+                        .ok_or_else(|| ParseCycleError::InvalidSymbol(e.to_string()))
+                })
+                .try_fold(0, |n, e| e.map(|e: usize| n.max(e)))?
+        } else {
+            symbols(trimmed.split(['\n', '/']).next().unwrap_or("")).len()
+        };
+        let ground = (1..=n)
+            .map(|i| T::try_from(i).map_err(|_| ParseCycleError::InvalidSymbol(i.to_string())))
+            .collect::<Result<Vec<T>, ParseCycleError>>()?;
+        Cycle::parse(trimmed, ground)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::cycle::{Cycle, PermutationError};
+    use crate::group_theory::notation::ParseCycleError;
+
+    #[test]
+    fn parse_cycles() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let f = Cycle::from(vec![vec![1, 5], vec![2, 4]], ground.clone());
+        debug_assert_eq!(
+            Cycle::parse_cycles("(1,5)(2,4)", ground.clone()),
+            Ok(f.clone())
+        );
+        debug_assert_eq!(
+            Cycle::parse_cycles("(1 5) (2 4)", ground.clone()),
+            Ok(f.clone())
+        );
+        debug_assert_eq!(Cycle::parse_cycles(&f.display(), ground.clone()), Ok(f));
+        debug_assert_eq!(
+            Cycle::parse_cycles("()", ground.clone()),
+            Ok(Cycle::identity(ground.clone()))
+        );
+    }
+
+    #[test]
+    fn parse_one_line() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let f = Cycle::from(vec![vec![1, 5], vec![2, 4]], ground.clone());
+        debug_assert_eq!(
+            Cycle::parse_one_line("5,4,3,2,1", ground.clone()),
+            Ok(f.clone())
+        );
+        debug_assert_eq!(
+            Cycle::parse_one_line(&f.get_retraversal_str(), ground.clone()),
+            Ok(f)
+        );
+    }
+
+    #[test]
+    fn parse_two_line() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let f = Cycle::from(vec![vec![1, 2, 3]], ground.clone());
+        debug_assert_eq!(
+            Cycle::parse_two_line("1 2 3 4 5\n2 3 1 4 5", ground.clone()),
+            Ok(f.clone())
+        );
+        debug_assert_eq!(
+            Cycle::parse_two_line("3,1,2,5,4 / 1,2,3,5,4", ground.clone()),
+            Ok(f.clone())
+        );
+        debug_assert_eq!(Cycle::parse("1 2 3 4 5 / 2 3 1 4 5", ground.clone()), Ok(f));
+    }
+
+    #[test]
+    fn from_str() {
+        let f: Cycle<usize> = "5,4,3,2,1".parse().unwrap();
+        debug_assert_eq!(
+            f,
+            Cycle::from(vec![vec![1, 5], vec![2, 4]], vec![1, 2, 3, 4, 5])
+        );
+        let g: Cycle<usize> = "(1,3)(2,4)".parse().unwrap();
+        debug_assert_eq!(g.n(), 4);
+        debug_assert_eq!(g.get_retraversal_str(), "3,4,1,2");
+        debug_assert!("(1,-3)".parse::<Cycle<i32>>().is_err());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let ground = vec![1, 2, 3, 4, 5];
+        debug_assert!(matches!(
+            Cycle::parse_cycles("(1,2", ground.clone()),
+            Err(ParseCycleError::Malformed { position: 0, .. })
+        ));
+        debug_assert!(matches!(
+            Cycle::parse_cycles("(1,(2))", ground.clone()),
+            Err(ParseCycleError::Malformed { .. })
+        ));
+        debug_assert!(matches!(
+            Cycle::parse_cycles("1,2", ground.clone()),
+            Err(ParseCycleError::Malformed { .. })
+        ));
+        debug_assert_eq!(
+            Cycle::parse_cycles("(1,2)(2,3)", ground.clone()),
+            Err(ParseCycleError::Invalid(PermutationError::Repeated(
+                String::from("2")
+            )))
+        );
+        debug_assert_eq!(
+            Cycle::parse_cycles("(1,6)", ground.clone()),
+            Err(ParseCycleError::Invalid(PermutationError::NotInGround(
+                String::from("6")
+            )))
+        );
+        debug_assert_eq!(
+            Cycle::parse_one_line("5,4,x,2,1", ground.clone()),
+            Err(ParseCycleError::InvalidSymbol(String::from("x")))
+        );
+        debug_assert_eq!(
+            Cycle::parse_one_line("5,4,3", ground.clone()),
+            Err(ParseCycleError::Invalid(PermutationError::LengthMismatch {
+                expected: 5,
+                found: 3
+            }))
+        );
+        debug_assert_eq!(
+            Cycle::parse_one_line("5,4,4,2,1", ground.clone()),
+            Err(ParseCycleError::Invalid(PermutationError::Repeated(
+                String::from("4")
+            )))
+        );
+    }
+}
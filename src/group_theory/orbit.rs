@@ -0,0 +1,385 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use indexmap::{IndexMap, IndexSet};
+
+use crate::group_theory::action::{GroupAction, OnPoints, OnSubsets, OnValues};
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::group::Group;
+use crate::group_theory::permutation_group::PermutationGroup;
+
+fn to_indices<V>(ground: &GroundSet<V>, points: &[V]) -> Vec<u32>
+where
+    V: Clone + Hash + Eq + Debug,
+{
+    points
+        .iter()
+        .map(|p| {
+            ground
+                .index_of(p)
+                .unwrap_or_else(|| panic!("{:?} is not in the ground set", p))
+        })
+        .collect()
+}
+
+fn to_points<V>(ground: &GroundSet<V>, indices: &[u32]) -> Vec<V>
+where
+    V: Clone + Hash + Eq,
+{
+    indices.iter().map(|&i| ground[i].clone()).collect()
+}
+
+/// The same permutations, moving the indices 0..n of the ground set instead of its elements. Points are worked with
+/// as indices, so a point is moved with OnPoints, a subset (sorted, since the indices are in ground order) with
+/// OnSubsets and a tuple with OnValues.
+fn on_indices<V>(ground: &GroundSet<V>, cycles: &[Cycle<V>]) -> Vec<Cycle<u32>>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    let indices = GroundSet::new((0..ground.len() as u32).collect());
+    cycles
+        .iter()
+        .map(|g| Cycle::from_images(g.images().to_vec(), &indices))
+        .collect()
+}
+
+/// The k-subsets of 0..n in lexicographic order.
+fn subsets(n: usize, k: usize) -> Vec<Vec<u32>> {
+    match k {
+        0 => vec![Vec::new()],
+        _ if k > n => Vec::new(),
+        _ => {
+            // the ones without n - 1, then the ones with it
+            let mut without = subsets(n - 1, k);
+            without.extend(subsets(n - 1, k - 1).into_iter().map(|mut s| {
+                s.push((n - 1) as u32);
+                s
+            }));
+            without
+        }
+    }
+}
+
+/// The k-tuples of distinct elements of 0..n.
+fn tuples(n: usize, k: usize) -> Vec<Vec<u32>> {
+    (0..k).fold(vec![Vec::new()], |acc, _| {
+        acc.into_iter()
+            .flat_map(|t| {
+                (0..n as u32)
+                    .filter(|i| !t.contains(i))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .map(move |i| {
+                        let mut next = t.clone();
+                        next.push(i);
+                        next
+                    })
+            })
+            .collect()
+    })
+}
+
+/// Everything the generators can move start to, in the order it is reached.
+fn orbit_of<X, A>(generators: &[Cycle<u32>], start: X::Owned, action: &A) -> Vec<X::Owned>
+where
+    X: ToOwned + ?Sized,
+    X::Owned: Clone + Hash + Eq,
+    A: GroupAction<Cycle<u32>, X>,
+{
+    let mut orbit = IndexSet::from([start]);
+    let mut i = 0;
+    while let Some(x) = orbit.get_index(i).cloned() {
+        for s in generators.iter() {
+            orbit.insert(action.act(s, x.borrow()));
+        }
+        i += 1;
+    }
+    orbit.into_iter().collect()
+}
+
+/// Splits everything into orbits, in the order of their first elements.
+fn partition<X, A>(generators: &[Cycle<u32>], all: Vec<X::Owned>, action: &A) -> Vec<Vec<X::Owned>>
+where
+    X: ToOwned + ?Sized,
+    X::Owned: Clone + Hash + Eq,
+    A: GroupAction<Cycle<u32>, X>,
+{
+    let mut seen = IndexSet::new();
+    let mut orbits = Vec::new();
+    for x in all {
+        if !seen.contains(&x) {
+            let orbit = orbit_of(generators, x, action);
+            seen.extend(orbit.iter().cloned());
+            orbits.push(orbit);
+        }
+    }
+    orbits
+}
+
+/// Schreier's lemma: if u_x sends start to x, the u_(s x)^-1 * s * u_x over every x in the orbit and generator s
+/// generate the stabilizer of start.
+fn stabilizer<V, X, A>(
+    generators: &[Cycle<V>],
+    identity: Cycle<V>,
+    start: X::Owned,
+    action: &A,
+) -> PermutationGroup<V>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+    X: ToOwned + ?Sized,
+    X::Owned: Clone + Hash + Eq,
+    A: GroupAction<Cycle<u32>, X>,
+{
+    let ground = identity.ground_set().clone();
+    let generators = on_indices(&ground, generators);
+    let identity = on_indices(&ground, std::slice::from_ref(&identity)).remove(0);
+    let mut transversal = IndexMap::from([(start, identity.clone())]);
+    let mut i = 0;
+    while let Some((x, u)) = transversal
+        .get_index(i)
+        .map(|(x, u)| (x.clone(), u.clone()))
+    {
+        for s in generators.iter() {
+            transversal
+                .entry(action.act(s, x.borrow()))
+                .or_insert_with(|| s * &u);
+        }
+        i += 1;
+    }
+    let mut schreier = IndexSet::new();
+    for (x, u) in transversal.iter() {
+        for s in generators.iter() {
+            let g = &(&transversal[&action.act(s, x.borrow())].inverse() * s) * u;
+            if g != identity {
+                schreier.insert(Cycle::from_images(g.images().to_vec(), &ground));
+            }
+        }
+    }
+    PermutationGroup::new(schreier.into_iter().collect(), ground)
+}
+
+/// Orbits, stabilizers and cosets, for any group of cycles. Points are elements of the ground set, subsets are
+/// listed in ground order and tuples have distinct entries. Everything panics on points outside the ground set.
+pub trait Orbits<V>: Group<Cycle<V>>
+where
+    V: Clone + Hash + Eq + Debug + Send + Sync + 'static,
+{
+    /// Every element the group can move the point to.
+    fn orbit(&self, point: &V) -> Vec<V> {
+        let ground = self.identity().ground_set().clone();
+        let start = to_indices(&ground, std::slice::from_ref(point))[0];
+        let generators = on_indices(&ground, &self.get_generator());
+        to_points(&ground, &orbit_of(&generators, start, &OnPoints))
+    }
+
+    /// The ground set split into orbits.
+    fn orbits(&self) -> Vec<Vec<V>> {
+        let ground = self.identity().ground_set().clone();
+        let generators = on_indices(&ground, &self.get_generator());
+        partition(&generators, (0..ground.len() as u32).collect(), &OnPoints)
+            .iter()
+            .map(|orbit| to_points(&ground, orbit))
+            .collect()
+    }
+
+    /// Every set the group can move the set to.
+    fn subset_orbit(&self, set: &[V]) -> Vec<Vec<V>> {
+        let ground = self.identity().ground_set().clone();
+        let mut start = to_indices(&ground, set);
+        start.sort_unstable();
+        start.dedup();
+        let generators = on_indices(&ground, &self.get_generator());
+        orbit_of(&generators, start, &OnSubsets)
+            .iter()
+            .map(|s| to_points(&ground, s))
+            .collect()
+    }
+
+    /// The k-subsets of the ground set split into orbits.
+    fn subset_orbits(&self, k: usize) -> Vec<Vec<Vec<V>>> {
+        let ground = self.identity().ground_set().clone();
+        let generators = on_indices(&ground, &self.get_generator());
+        partition(&generators, subsets(ground.len(), k), &OnSubsets)
+            .iter()
+            .map(|orbit| orbit.iter().map(|s| to_points(&ground, s)).collect())
+            .collect()
+    }
+
+    /// Every tuple the group can move the tuple to, position by position.
+    fn tuple_orbit(&self, tuple: &[V]) -> Vec<Vec<V>> {
+        let ground = self.identity().ground_set().clone();
+        let start = to_indices(&ground, tuple);
+        let generators = on_indices(&ground, &self.get_generator());
+        orbit_of(&generators, start, &OnValues)
+            .iter()
+            .map(|t| to_points(&ground, t))
+            .collect()
+    }
+
+    /// The k-tuples of distinct elements split into orbits. There is one orbit exactly when the group is k-transitive.
+    fn tuple_orbits(&self, k: usize) -> Vec<Vec<Vec<V>>> {
+        let ground = self.identity().ground_set().clone();
+        let generators = on_indices(&ground, &self.get_generator());
+        partition(&generators, tuples(ground.len(), k), &OnValues)
+            .iter()
+            .map(|orbit| orbit.iter().map(|t| to_points(&ground, t)).collect())
+            .collect()
+    }
+
+    /// The elements fixing the point.
+    fn point_stabilizer(&self, point: &V) -> PermutationGroup<V> {
+        let identity = self.identity();
+        let start = to_indices(identity.ground_set(), std::slice::from_ref(point))[0];
+        stabilizer(&self.get_generator(), identity, start, &OnPoints)
+    }
+
+    /// The elements mapping the set onto itself, eg the ones keeping the first c elements of a trace in a cache of size c.
+    fn set_stabilizer(&self, set: &[V]) -> PermutationGroup<V> {
+        let identity = self.identity();
+        let mut start = to_indices(identity.ground_set(), set);
+        start.sort_unstable();
+        start.dedup();
+        stabilizer(&self.get_generator(), identity, start, &OnSubsets)
+    }
+
+    /// The elements fixing every point of the tuple.
+    fn tuple_stabilizer(&self, tuple: &[V]) -> PermutationGroup<V> {
+        let identity = self.identity();
+        let start = to_indices(identity.ground_set(), tuple);
+        stabilizer(&self.get_generator(), identity, start, &OnValues)
+    }
+
+    /// One element from each left coset g * subgroup, starting with the identity. The subgroup has to be inside the group.
+    fn left_coset_representatives(&self, subgroup: &PermutationGroup<V>) -> Vec<Cycle<V>> {
+        let generators = self.get_generator();
+        let identity = self.identity();
+        let mut cosets = IndexMap::from([(subgroup.canonical_left_coset(&identity), identity)]);
+        let mut i = 0;
+        // the group acts on its left cosets by multiplying on the left
+        while let Some(r) = cosets.get_index(i).map(|(_, r)| r.clone()) {
+            for s in generators.iter() {
+                let g = s * &r;
+                cosets.entry(subgroup.canonical_left_coset(&g)).or_insert(g);
+            }
+            i += 1;
+        }
+        cosets.into_values().collect()
+    }
+
+    /// One element from each right coset subgroup * g, starting with the identity. The subgroup has to be inside the group.
+    fn right_coset_representatives(&self, subgroup: &PermutationGroup<V>) -> Vec<Cycle<V>> {
+        let generators = self.get_generator();
+        let identity = self.identity();
+        let mut cosets = IndexMap::from([(subgroup.canonical_right_coset(&identity), identity)]);
+        let mut i = 0;
+        while let Some(r) = cosets.get_index(i).map(|(_, r)| r.clone()) {
+            for s in generators.iter() {
+                let g = &r * s;
+                cosets
+                    .entry(subgroup.canonical_right_coset(&g))
+                    .or_insert(g);
+            }
+            i += 1;
+        }
+        cosets.into_values().collect()
+    }
+}
+
+impl<V, G> Orbits<V> for G
+where
+    G: Group<Cycle<V>> + ?Sized,
+    V: Clone + Hash + Eq + Debug + Send + Sync + 'static,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::dihedral::dihedral;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::orbit::Orbits;
+    use crate::group_theory::permutation_group::PermutationGroup;
+    use crate::group_theory::symmetric::sym;
+
+    #[test]
+    fn point_orbits() {
+        let ground: Vec<usize> = (1..=6).collect();
+        let group = PermutationGroup::new(
+            vec![
+                Cycle::from(vec![vec![1, 2, 3]], &ground),
+                Cycle::from(vec![vec![4, 5]], &ground),
+            ],
+            &ground,
+        );
+        debug_assert_eq!(group.orbit(&2), vec![2, 3, 1]);
+        debug_assert_eq!(group.orbits(), vec![vec![1, 2, 3], vec![4, 5], vec![6]]);
+        debug_assert_eq!(group.point_stabilizer(&1).order(), 2);
+    }
+
+    #[test]
+    fn dihedral_subsets_and_tuples() {
+        let d_5 = dihedral(5);
+        // the 2-subsets of a pentagon are its 5 edges and 5 diagonals
+        let pairs = d_5.subset_orbits(2);
+        debug_assert_eq!(pairs.len(), 2);
+        debug_assert!(pairs.iter().all(|orbit| orbit.len() == 5));
+        debug_assert_eq!(d_5.subset_orbit(&[2, 1]).len(), 5);
+        debug_assert!(d_5.subset_orbit(&[1, 2]).contains(&vec![1, 5]));
+        // D_5 is 1-transitive but not 2-transitive
+        debug_assert_eq!(d_5.tuple_orbits(1).len(), 1);
+        debug_assert_eq!(d_5.tuple_orbits(2).len(), 2);
+        debug_assert_eq!(d_5.tuple_orbit(&[1, 2]).len(), 10);
+        debug_assert_eq!(d_5.set_stabilizer(&[1, 2]).order(), 2);
+        debug_assert_eq!(d_5.tuple_stabilizer(&[1, 2]).order(), 1);
+        debug_assert_eq!(sym(4).tuple_orbits(3).len(), 1);
+    }
+
+    #[test]
+    fn stabilizers_of_symmetric() {
+        let s_5 = sym(5);
+        debug_assert_eq!(s_5.point_stabilizer(&3).order(), 24);
+        debug_assert_eq!(s_5.tuple_stabilizer(&[1, 2]).order(), 6);
+        // keeping the first two accessed elements inside a cache of size 2: S_2 x S_3
+        let window = s_5.set_stabilizer(&[1, 2]);
+        debug_assert_eq!(window.order(), 12);
+        debug_assert!(window.contains(&s_5.create_vec(vec![vec![1, 2], vec![3, 4, 5]])));
+        debug_assert!(!window.contains(&s_5.create_vec(vec![vec![2, 3]])));
+    }
+
+    #[test]
+    fn cosets_partition_the_group() {
+        let s_4 = sym(4);
+        let subgroup = s_4.set_stabilizer(&[1, 2]);
+        let left = s_4.left_coset_representatives(&subgroup);
+        let right = s_4.right_coset_representatives(&subgroup);
+        debug_assert_eq!(left.len(), 6);
+        debug_assert_eq!(right.len(), 6);
+        debug_assert_eq!(left[0], s_4.identity());
+        for g in s_4.get_set() {
+            let containing_left = left
+                .iter()
+                .filter(|r| subgroup.contains(&(&r.inverse() * &g)))
+                .count();
+            let containing_right = right
+                .iter()
+                .filter(|r| subgroup.contains(&(&g * &r.inverse())))
+                .count();
+            debug_assert_eq!(containing_left, 1);
+            debug_assert_eq!(containing_right, 1);
+        }
+        // a left coset g H sends {1, 2} to g({1, 2}), so there is one for every 2-subset
+        let mut images: Vec<Vec<usize>> = left
+            .iter()
+            .map(|r| {
+                let mut image = vec![r.eval(1), r.eval(2)];
+                image.sort();
+                image
+            })
+            .collect();
+        images.sort();
+        images.dedup();
+        debug_assert_eq!(images.len(), 6);
+    }
+}
@@ -0,0 +1,366 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Range;
+
+use rand::Rng;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::group::Group;
+
+/// (a * b)(x) = a(b(x)), the same right to left composition as Cycle's Mul.
+fn compose(a: &[u32], b: &[u32]) -> Vec<u32> {
+    b.iter().map(|&x| a[x as usize]).collect()
+}
+
+fn invert(a: &[u32]) -> Vec<u32> {
+    let mut inv = vec![0; a.len()];
+    for (i, &j) in a.iter().enumerate() {
+        inv[j as usize] = i as u32;
+    }
+    inv
+}
+
+fn is_identity(a: &[u32]) -> bool {
+    a.iter().enumerate().all(|(i, &j)| i as u32 == j)
+}
+
+/// One step of the stabilizer chain G = G_0 >= G_1 >= ... >= G_k = 1, where G_(i+1) fixes the i-th base point.
+struct Level {
+    /// The base point this level moves around.
+    point: u32,
+    /// Strong generators of G_i, all of which fix the earlier base points.
+    generators: Vec<Vec<u32>>,
+    /// The orbit of the base point under G_i, in the order it was found.
+    orbit: Vec<u32>,
+    /// transversal[p] maps the base point to p, for every p in the orbit.
+    transversal: Vec<Option<Vec<u32>>>,
+}
+
+impl Level {
+    fn new(point: u32, n: usize) -> Level {
+        let mut transversal = vec![None; n];
+        transversal[point as usize] = Some((0..n as u32).collect());
+        Level {
+            point,
+            generators: Vec::new(),
+            orbit: vec![point],
+            transversal,
+        }
+    }
+
+    /// Extends the orbit (and transversal) after new generators were added.
+    fn grow_orbit(&mut self) {
+        let mut i = 0;
+        // every point has to be rescanned, since a new generator can take old points somewhere new
+        while i < self.orbit.len() {
+            let p = self.orbit[i];
+            for s in self.generators.iter() {
+                let q = s[p as usize];
+                if self.transversal[q as usize].is_none() {
+                    let u = compose(s, self.transversal[p as usize].as_ref().unwrap());
+                    self.transversal[q as usize] = Some(u);
+                    self.orbit.push(q);
+                }
+            }
+            i += 1;
+        }
+    }
+}
+
+/// The subgroup of Sym(ground) generated by some permutations.
+/// Built with the (deterministic) Schreier-Sims algorithm, which finds a base b_1, ..., b_k and a strong
+/// generating set, so every element factors uniquely as u_1 * ... * u_k with u_i from the transversal of level i.
+/// That gives the exact order, membership testing and uniform sampling without ever listing the group.
+pub struct PermutationGroup<V>
+where
+    V: Clone + Hash + Eq + 'static,
+{
+    ground: GroundSet<V>,
+    generators: Vec<Cycle<V>>,
+    levels: Vec<Level>,
+}
+
+impl<V> PermutationGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + 'static,
+{
+    /// The group generated by the cycles, which all have to be over the ground set.
+    pub fn new(generators: Vec<Cycle<V>>, ground: impl Into<GroundSet<V>>) -> PermutationGroup<V> {
+        let ground = ground.into();
+        assert!(
+            generators.iter().all(|g| g.ground_set() == &ground),
+            "generators have to be over the ground set of the group"
+        );
+        let mut group = PermutationGroup {
+            ground,
+            generators,
+            levels: Vec::new(),
+        };
+        let images: Vec<Vec<u32>> = group
+            .generators
+            .iter()
+            .map(|g| g.images().to_vec())
+            .filter(|g| !is_identity(g))
+            .collect();
+        for g in images {
+            // sifting first skips generators that are already in the group built so far
+            let residue = group.sift(g, 0);
+            if !is_identity(&residue) {
+                group.extend(0, residue);
+            }
+        }
+        group
+    }
+
+    pub fn ground_set(&self) -> &GroundSet<V> {
+        &self.ground
+    }
+
+    /// Adds g (which fixes the first i base points) to the generators of level i,
+    /// then makes sure every Schreier generator of level i is in the levels below it.
+    fn extend(&mut self, i: usize, g: Vec<u32>) {
+        if i == self.levels.len() {
+            let point = (0..g.len()).find(|&p| g[p] != p as u32).unwrap() as u32;
+            self.levels.push(Level::new(point, self.ground.len()));
+        }
+        self.levels[i].generators.push(g);
+        self.levels[i].grow_orbit();
+
+        // only the levels further down change while this runs, and they only grow,
+        // so a Schreier generator that sifted through once keeps sifting through
+        let orbit = self.levels[i].orbit.clone();
+        for s in self.levels[i].generators.clone() {
+            for &p in orbit.iter() {
+                let level = &self.levels[i];
+                let u_p = level.transversal[p as usize].as_ref().unwrap();
+                let u_sp = level.transversal[s[p as usize] as usize].as_ref().unwrap();
+                // u_sp^-1 * s * u_p sends the base point to itself
+                let schreier = compose(&invert(u_sp), &compose(&s, u_p));
+                let residue = self.sift(schreier, i + 1);
+                if !is_identity(&residue) {
+                    self.extend(i + 1, residue);
+                }
+            }
+        }
+    }
+
+    /// Divides g by transversal elements level by level, starting at level i.
+    /// Returns what is left, which is the identity exactly when g was in G_i.
+    fn sift(&self, mut g: Vec<u32>, i: usize) -> Vec<u32> {
+        for level in self.levels[i..].iter() {
+            match &level.transversal[g[level.point as usize] as usize] {
+                Some(u) => g = compose(&invert(u), &g),
+                None => return g,
+            }
+        }
+        g
+    }
+
+    pub fn contains(&self, cycle: &Cycle<V>) -> bool {
+        cycle.ground_set() == &self.ground && is_identity(&self.sift(cycle.images().to_vec(), 0))
+    }
+
+    /// The base points, in order. Only the identity fixes all of them.
+    pub fn base(&self) -> Vec<V> {
+        self.levels
+            .iter()
+            .map(|level| self.ground[level.point].clone())
+            .collect()
+    }
+
+    /// A generating set whose elements fixing the first i base points generate the stabilizer of those points.
+    pub fn strong_generators(&self) -> Vec<Cycle<V>> {
+        self.levels
+            .iter()
+            .flat_map(|level| level.generators.iter())
+            .map(|g| Cycle::from_images(g.clone(), &self.ground))
+            .collect()
+    }
+
+    /// The element of the left coset g * self whose images of the base points come first in ground order.
+    /// Every element of the coset gives the same one, so it names the coset.
+    pub fn canonical_left_coset(&self, g: &Cycle<V>) -> Cycle<V> {
+        let mut image = g.images().to_vec();
+        // what is still free at level i only moves the i-th base point around its orbit
+        for level in self.levels.iter() {
+            let p = *level
+                .orbit
+                .iter()
+                .min_by_key(|&&p| image[p as usize])
+                .unwrap();
+            image = compose(&image, level.transversal[p as usize].as_ref().unwrap());
+        }
+        Cycle::from_images(image, &self.ground)
+    }
+
+    /// The same for the right coset self * g, which is the inverse of the left coset g^-1 * self.
+    pub fn canonical_right_coset(&self, g: &Cycle<V>) -> Cycle<V> {
+        self.canonical_left_coset(&g.inverse()).inverse()
+    }
+
+    /// The k-th element of the group, reading k in the mixed radix of the orbit sizes, last level first.
+    fn element(&self, mut k: u128) -> Vec<u32> {
+        let mut image: Vec<u32> = (0..self.ground.len() as u32).collect();
+        for level in self.levels.iter().rev() {
+            let len = level.orbit.len() as u128;
+            let p = level.orbit[(k % len) as usize];
+            k /= len;
+            image = compose(level.transversal[p as usize].as_ref().unwrap(), &image);
+        }
+        image
+    }
+
+    /// An element picked uniformly at random, by picking a transversal element of each level uniformly.
+    pub fn random_element<R: Rng + ?Sized>(&self, rng: &mut R) -> Cycle<V> {
+        let image = self.levels.iter().fold(
+            (0..self.ground.len() as u32).collect(),
+            |acc: Vec<u32>, level| {
+                let p = level.orbit[rng.gen_range(0..level.orbit.len())];
+                compose(&acc, level.transversal[p as usize].as_ref().unwrap())
+            },
+        );
+        Cycle::from_images(image, &self.ground)
+    }
+}
+
+impl<V> Group<Cycle<V>> for PermutationGroup<V>
+where
+    V: Clone + Hash + Eq + PartialEq + Debug + Send + Sync + 'static,
+{
+    /// Every product u_1 * ... * u_k of transversal elements, each of which is a different element.
+    fn elements(&self) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        Box::new((0..self.order()).map(|k| Cycle::from_images(self.element(k), &self.ground)))
+    }
+
+    /// Positions are mixed radix numbers over the stabilizer chain, so any of them can be built directly.
+    fn elements_range(
+        &self,
+        positions: Range<u128>,
+    ) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        let end = positions.end.min(self.order());
+        Box::new((positions.start..end).map(|k| Cycle::from_images(self.element(k), &self.ground)))
+    }
+
+    fn seekable(&self) -> bool {
+        true
+    }
+
+    fn op(&self, a: Cycle<V>, b: Cycle<V>) -> Cycle<V> {
+        a * b
+    }
+
+    fn identity(&self) -> Cycle<V> {
+        Cycle::identity(&self.ground)
+    }
+
+    fn inverse(&self, e: Cycle<V>) -> Cycle<V> {
+        e.inverse()
+    }
+
+    /// The product of the orbit sizes along the stabilizer chain.
+    fn order(&self) -> u128 {
+        self.levels
+            .iter()
+            .try_fold(1u128, |acc, level| {
+                acc.checked_mul(level.orbit.len() as u128)
+            })
+            .expect("the order of the group doesn't fit in a u128")
+    }
+
+    fn get_generator(&self) -> Vec<Cycle<V>> {
+        self.generators.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::permutation_group::PermutationGroup;
+    use crate::group_theory::symmetric::sym;
+
+    #[test]
+    fn symmetric_from_two_generators() {
+        let ground: Vec<usize> = (1..=7).collect();
+        let group = PermutationGroup::new(
+            vec![
+                Cycle::from(vec![vec![1, 2]], &ground),
+                Cycle::from(vec![vec![1, 2, 3, 4, 5, 6, 7]], &ground),
+            ],
+            &ground,
+        );
+        debug_assert_eq!(group.order(), 5040);
+        let s_7 = sym(7);
+        debug_assert!(group.contains(&Cycle::from_retraversal(&[7, 6, 5, 4, 3, 2, 1], &ground)));
+        debug_assert_eq!(group.get_set().len(), s_7.get_set().len());
+    }
+
+    #[test]
+    fn dihedral_membership() {
+        let ground = vec![1, 2, 3, 4];
+        let rotation = Cycle::from(vec![vec![1, 2, 3, 4]], &ground);
+        let reflection = Cycle::from(vec![vec![1, 3]], &ground);
+        let group = PermutationGroup::new(vec![rotation, reflection], &ground);
+        debug_assert_eq!(group.order(), 8);
+        debug_assert!(group.contains(&Cycle::from(vec![vec![2, 4]], &ground)));
+        debug_assert!(group.contains(&Cycle::from(vec![vec![1, 2], vec![3, 4]], &ground)));
+        debug_assert!(!group.contains(&Cycle::from(vec![vec![1, 2]], &ground)));
+        let brute: HashSet<Cycle<usize>> = sym(4)
+            .get_set()
+            .into_iter()
+            .map(|g| Cycle::from_images(g.images().to_vec(), &ground))
+            .filter(|g| group.contains(g))
+            .collect();
+        debug_assert_eq!(group.get_set(), brute);
+        let base = group.base();
+        debug_assert!(group
+            .get_set()
+            .iter()
+            .filter(|g| base.iter().all(|&b| g.eval(b) == b))
+            .all(|g| *g == group.identity()));
+    }
+
+    #[test]
+    fn alternating_from_three_cycles() {
+        let ground: Vec<usize> = (1..=6).collect();
+        let gens = (1..=4)
+            .map(|i| Cycle::from(vec![vec![i, i + 1, i + 2]], &ground))
+            .collect();
+        let group = PermutationGroup::new(gens, &ground);
+        debug_assert_eq!(group.order(), 360);
+        let set = group.get_set();
+        debug_assert_eq!(set.len(), 360);
+        debug_assert!(set.iter().all(|g| g.is_even()));
+        let trivial = PermutationGroup::new(vec![Cycle::identity(&ground)], &ground);
+        debug_assert_eq!(trivial.order(), 1);
+        debug_assert!(trivial.base().is_empty());
+    }
+
+    #[test]
+    fn random_elements_are_members() {
+        // the block swaps of {1, 2, 3} and {4, 5, 6}, with each block permuted freely: S_3 wr S_2
+        let ground: Vec<usize> = (1..=6).collect();
+        let group = PermutationGroup::new(
+            vec![
+                Cycle::from(vec![vec![1, 4], vec![2, 5], vec![3, 6]], &ground),
+                Cycle::from(vec![vec![1, 2]], &ground),
+                Cycle::from(vec![vec![1, 2, 3]], &ground),
+            ],
+            &ground,
+        );
+        debug_assert_eq!(group.order(), 72);
+        let mut rng = StdRng::seed_from_u64(7);
+        let samples: Vec<Cycle<usize>> = (0..500).map(|_| group.random_element(&mut rng)).collect();
+        debug_assert!(samples.iter().all(|g| group.contains(g)));
+        // 500 draws from 72 elements should hit nearly all of them
+        let distinct: HashSet<_> = samples.into_iter().collect();
+        debug_assert!(distinct.len() > 60);
+        debug_assert!(!group.contains(&Cycle::from(vec![vec![1, 5]], &ground)));
+    }
+}
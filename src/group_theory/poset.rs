@@ -0,0 +1,302 @@
+use std::cmp::Ordering;
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::symmetric::SymmetricGroup;
+
+/// The partial orders on S_n, all graded by the number of inversions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermutationOrder {
+    /// u <= v when v = x * u with the inversions adding up, ie climbing by multiplying generators on the left.
+    LeftWeak,
+    /// u <= v when v = u * x with the inversions adding up, ie climbing by multiplying generators on the right.
+    RightWeak,
+    /// u <= v when some reduced word of v has a reduced word of u as a subword.
+    Bruhat,
+}
+
+/// Checks the inversions of the pairs of values: (a, b) with a < b is an inversion when b comes before a.
+/// These are what the right weak order compares.
+fn value_inversion(position: &[u32], a: usize, b: usize) -> bool {
+    position[a] > position[b]
+}
+
+/// The permutation whose value inversions are the transitive closure of the value inversions of u and v.
+fn join_images(u: &[u32], v: &[u32]) -> Vec<u32> {
+    let n = u.len();
+    let (pos_u, pos_v) = (inverse_images(u), inverse_images(v));
+    let mut inverted: Vec<Vec<bool>> = (0..n)
+        .map(|a| {
+            (0..n)
+                .map(|b| a < b && (value_inversion(&pos_u, a, b) || value_inversion(&pos_v, a, b)))
+                .collect()
+        })
+        .collect();
+    // (a, b) and (b, c) force (a, c) when a < b < c, so close over the middle value
+    for b in 0..n {
+        for a in 0..b {
+            for c in b + 1..n {
+                if inverted[a][b] && inverted[b][c] {
+                    inverted[a][c] = true;
+                }
+            }
+        }
+    }
+    let mut values: Vec<u32> = (0..n as u32).collect();
+    values.sort_by(|&x, &y| {
+        let (a, b) = (x.min(y) as usize, x.max(y) as usize);
+        match (x < y, inverted[a][b]) {
+            (_, _) if x == y => Ordering::Equal,
+            (true, false) | (false, true) => Ordering::Less,
+            _ => Ordering::Greater,
+        }
+    });
+    values
+}
+
+fn inverse_images(image: &[u32]) -> Vec<u32> {
+    let mut inv = vec![0; image.len()];
+    for (i, &j) in image.iter().enumerate() {
+        inv[j as usize] = i as u32;
+    }
+    inv
+}
+
+impl<T> Cycle<T>
+where
+    T: Debug + Clone + Eq + Hash + 'static,
+{
+    pub fn left_weak_le(&self, other: &Cycle<T>) -> bool {
+        (other * &self.inverse()).inversions() + self.inversions() == other.inversions()
+    }
+
+    pub fn right_weak_le(&self, other: &Cycle<T>) -> bool {
+        (&self.inverse() * other).inversions() + self.inversions() == other.inversions()
+    }
+
+    /// The tableau criterion: for every prefix, the sorted images of self are at most the sorted images of other.
+    pub fn bruhat_le(&self, other: &Cycle<T>) -> bool {
+        let mut mine = Vec::with_capacity(self.n());
+        let mut theirs = Vec::with_capacity(other.n());
+        self.images()
+            .iter()
+            .zip(other.images().iter())
+            .all(|(&a, &b)| {
+                let i = mine.partition_point(|&x| x < a);
+                mine.insert(i, a);
+                let j = theirs.partition_point(|&x| x < b);
+                theirs.insert(j, b);
+                mine.iter().zip(theirs.iter()).all(|(x, y)| x <= y)
+            })
+    }
+
+    pub fn le(&self, other: &Cycle<T>, order: PermutationOrder) -> bool {
+        match order {
+            PermutationOrder::LeftWeak => self.left_weak_le(other),
+            PermutationOrder::RightWeak => self.right_weak_le(other),
+            PermutationOrder::Bruhat => self.bruhat_le(other),
+        }
+    }
+
+    /// The least upper bound in the right weak order.
+    pub fn right_weak_join(&self, other: &Cycle<T>) -> Self {
+        Cycle::from_images(
+            join_images(self.images(), other.images()),
+            self.ground_set(),
+        )
+    }
+
+    /// The greatest lower bound in the right weak order.
+    /// Putting the reversal on the left flips the right weak order upside down, so this is a join in disguise.
+    pub fn right_weak_meet(&self, other: &Cycle<T>) -> Self {
+        let n = self.n() as u32;
+        let flip = |c: &Cycle<T>| c.images().iter().map(|&i| n - 1 - i).collect::<Vec<u32>>();
+        let join = join_images(&flip(self), &flip(other));
+        Cycle::from_images(join.iter().map(|&i| n - 1 - i).collect(), self.ground_set())
+    }
+
+    /// The least upper bound in the left weak order, which is the right weak order on the inverses.
+    pub fn left_weak_join(&self, other: &Cycle<T>) -> Self {
+        self.inverse().right_weak_join(&other.inverse()).inverse()
+    }
+
+    /// The greatest lower bound in the left weak order.
+    pub fn left_weak_meet(&self, other: &Cycle<T>) -> Self {
+        self.inverse().right_weak_meet(&other.inverse()).inverse()
+    }
+
+    /// The elements that cover self in the order, ie are one inversion above it.
+    pub fn covers(&self, order: PermutationOrder) -> Vec<Self> {
+        let n = self.n();
+        let swapped = |i: usize, j: usize| {
+            let mut image = self.images().to_vec();
+            image.swap(i, j);
+            Cycle::from_images(image, self.ground_set())
+        };
+        let image = self.images();
+        match order {
+            PermutationOrder::RightWeak => (1..n)
+                .filter(|&i| image[i - 1] < image[i])
+                .map(|i| swapped(i - 1, i))
+                .collect(),
+            PermutationOrder::LeftWeak => {
+                let position = inverse_images(image);
+                (1..n)
+                    .filter(|&v| position[v - 1] < position[v])
+                    .map(|v| swapped(position[v - 1] as usize, position[v] as usize))
+                    .collect()
+            }
+            // swapping positions i < j covers when image[i] < image[j] and nothing in between has a value in between
+            PermutationOrder::Bruhat => (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                .filter(|&(i, j)| {
+                    image[i] < image[j]
+                        && image[i + 1..j]
+                            .iter()
+                            .all(|&k| k < image[i] || k > image[j])
+                })
+                .map(|(i, j)| swapped(i, j))
+                .collect(),
+        }
+    }
+}
+
+impl<V> SymmetricGroup<V>
+where
+    V: Clone + Copy + Hash + Eq + PartialEq + Debug + 'static,
+{
+    /// Every w with u <= w <= v, sorted by the number of inversions. Empty if u is not below v.
+    /// This climbs the covers from u, so it only touches the interval and never all of S_n.
+    pub fn interval(&self, u: &Cycle<V>, v: &Cycle<V>, order: PermutationOrder) -> Vec<Cycle<V>> {
+        if !u.le(v, order) {
+            return Vec::new();
+        }
+        let mut seen = HashSet::from([u.clone()]);
+        let mut q = VecDeque::from([u.clone()]);
+        while let Some(w) = q.pop_front() {
+            for cover in w.covers(order) {
+                if !seen.contains(&cover) && cover.le(v, order) {
+                    seen.insert(cover.clone());
+                    q.push_back(cover);
+                }
+            }
+        }
+        let mut interval: Vec<Cycle<V>> = seen.into_iter().collect();
+        interval.sort_by_cached_key(|w| (w.inversions(), w.images().to_vec()));
+        interval
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::poset::PermutationOrder;
+    use crate::group_theory::symmetric::sym;
+
+    const ORDERS: [PermutationOrder; 3] = [
+        PermutationOrder::LeftWeak,
+        PermutationOrder::RightWeak,
+        PermutationOrder::Bruhat,
+    ];
+
+    #[test]
+    fn relations_in_s3() {
+        let s_3 = sym(3);
+        let set: Vec<Cycle<usize>> = s_3.get_set().into_iter().collect();
+        let count = |order| {
+            set.iter()
+                .flat_map(|u| set.iter().map(move |v| (u, v)))
+                .filter(|(u, v)| u != v && u.le(v, order))
+                .count()
+        };
+        // the weak orders on S_3 are hexagons, and Bruhat adds the two relations s_i < s_j s_i
+        debug_assert_eq!(count(PermutationOrder::RightWeak), 11);
+        debug_assert_eq!(count(PermutationOrder::LeftWeak), 11);
+        debug_assert_eq!(count(PermutationOrder::Bruhat), 13);
+    }
+
+    #[test]
+    fn weak_sides() {
+        let s_4 = sym(4);
+        let u = s_4.create_retraversal(&[2, 1, 3, 4]);
+        let v = s_4.create_retraversal(&[2, 3, 1, 4]);
+        debug_assert!(u.right_weak_le(&v));
+        debug_assert!(!u.left_weak_le(&v));
+        debug_assert!(u.bruhat_le(&v));
+        debug_assert!(!v.bruhat_le(&u));
+    }
+
+    #[test]
+    fn intervals_match_brute_force() {
+        let s_4 = sym(4);
+        let set: Vec<Cycle<usize>> = s_4.get_set().into_iter().collect();
+        let pairs = [
+            (s_4.identity(), s_4.create_retraversal(&[4, 3, 2, 1])),
+            (s_4.identity(), s_4.create_retraversal(&[3, 2, 1, 4])),
+            (
+                s_4.create_retraversal(&[2, 1, 3, 4]),
+                s_4.create_retraversal(&[4, 2, 3, 1]),
+            ),
+            (
+                s_4.create_retraversal(&[1, 3, 2, 4]),
+                s_4.create_retraversal(&[3, 4, 1, 2]),
+            ),
+        ];
+        for order in ORDERS {
+            for (u, v) in pairs.iter() {
+                let interval = s_4.interval(u, v, order);
+                let expected = set
+                    .iter()
+                    .filter(|w| u.le(w, order) && w.le(v, order))
+                    .count();
+                debug_assert_eq!(interval.len(), expected);
+                debug_assert!(interval.iter().all(|w| u.le(w, order) && w.le(v, order)));
+            }
+        }
+        debug_assert_eq!(
+            s_4.interval(
+                &s_4.identity(),
+                &s_4.create_retraversal(&[4, 3, 2, 1]),
+                PermutationOrder::Bruhat
+            )
+            .len(),
+            24
+        );
+    }
+
+    #[test]
+    fn meets_and_joins() {
+        let s_4 = sym(4);
+        let set: Vec<Cycle<usize>> = s_4.get_set().into_iter().collect();
+        for u in set.iter() {
+            for v in set.iter() {
+                for (order, join, meet) in [
+                    (
+                        PermutationOrder::RightWeak,
+                        u.right_weak_join(v),
+                        u.right_weak_meet(v),
+                    ),
+                    (
+                        PermutationOrder::LeftWeak,
+                        u.left_weak_join(v),
+                        u.left_weak_meet(v),
+                    ),
+                ] {
+                    debug_assert!(u.le(&join, order) && v.le(&join, order));
+                    debug_assert!(meet.le(u, order) && meet.le(v, order));
+                    for w in set.iter() {
+                        if u.le(w, order) && v.le(w, order) {
+                            debug_assert!(join.le(w, order));
+                        }
+                        if w.le(u, order) && w.le(v, order) {
+                            debug_assert!(w.le(&meet, order));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
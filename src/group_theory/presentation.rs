@@ -0,0 +1,593 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::ground::GroundSet;
+use crate::group_theory::group::Group;
+use crate::group_theory::permutation_group::PermutationGroup;
+
+/// A word in the generators of a presentation, as (generator, exponent) pairs read left to right,
+/// so [(0, 2), (1, -1)] is f^2 g^-1 when the generators are f and g.
+pub type Word = Vec<(usize, i64)>;
+
+/// How many cosets FinitelyPresentedGroup::new may define before it gives up on the presentation.
+pub const DEFAULT_MAX_COSETS: usize = 1 << 20;
+
+/// Everything that can go wrong when turning a presentation into permutations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresentationError {
+    /// A word that doesn't follow the syntax, ie "(f g" or "f^".
+    Malformed { position: usize, reason: String },
+    /// A name, or index, that isn't one of the generators.
+    UnknownGenerator(String),
+    /// Coset enumeration defined this many cosets without closing the table. The group (or the index of the
+    /// subgroup) is infinite or just too large.
+    TooManyCosets(usize),
+}
+
+impl fmt::Display for PresentationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PresentationError::Malformed { position, reason } => {
+                write!(f, "malformed word at {}: {}", position, reason)
+            }
+            PresentationError::UnknownGenerator(s) => write!(f, "{:?} is not a generator", s),
+            PresentationError::TooManyCosets(n) => {
+                write!(f, "coset enumeration gave up after {} cosets", n)
+            }
+        }
+    }
+}
+
+impl Error for PresentationError {}
+
+/// w^-1, which reverses the word and negates every exponent.
+pub fn invert_word(word: &[(usize, i64)]) -> Word {
+    word.iter().rev().map(|&(g, e)| (g, -e)).collect()
+}
+
+/// Recursive descent over
+///   word   := factor*
+///   factor := atom ('^' integer)?
+///   atom   := name | '1' | '(' word ')'
+/// with whitespace or '*' between factors.
+struct WordParser<'a> {
+    chars: Vec<char>,
+    position: usize,
+    generators: &'a [&'a str],
+}
+
+impl WordParser<'_> {
+    fn malformed<T>(&self, reason: &str) -> Result<T, PresentationError> {
+        Err(PresentationError::Malformed {
+            position: self.position,
+            reason: reason.to_string(),
+        })
+    }
+
+    fn skip_separators(&mut self) {
+        while self
+            .chars
+            .get(self.position)
+            .is_some_and(|&c| c.is_whitespace() || c == '*')
+        {
+            self.position += 1;
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+        let start = self.position;
+        while self.chars.get(self.position).is_some_and(|&c| f(c)) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn word(&mut self) -> Result<Word, PresentationError> {
+        let mut word = Word::new();
+        loop {
+            self.skip_separators();
+            match self.chars.get(self.position) {
+                None | Some(')') | Some('=') => return Ok(word),
+                Some(_) => word.extend(self.factor()?),
+            }
+        }
+    }
+
+    fn factor(&mut self) -> Result<Word, PresentationError> {
+        let atom = self.atom()?;
+        self.skip_whitespace();
+        if self.chars.get(self.position) != Some(&'^') {
+            return Ok(atom);
+        }
+        self.position += 1;
+        self.skip_whitespace();
+        let digits = self.take_while(|c| c == '-' || c.is_ascii_digit());
+        let Ok(power) = digits.parse::<i64>() else {
+            return self.malformed("expected an integer exponent after '^'");
+        };
+        let base = if power < 0 { invert_word(&atom) } else { atom };
+        Ok(base.repeat(power.unsigned_abs() as usize))
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn atom(&mut self) -> Result<Word, PresentationError> {
+        match self.chars[self.position] {
+            '(' => {
+                self.position += 1;
+                let word = self.word()?;
+                if self.chars.get(self.position) != Some(&')') {
+                    return self.malformed("unclosed '('");
+                }
+                self.position += 1;
+                Ok(word)
+            }
+            '1' => {
+                self.position += 1;
+                Ok(Word::new())
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let name = self.take_while(|c| c.is_alphanumeric() || c == '_');
+                match self.generators.iter().position(|&g| g == name) {
+                    Some(i) => Ok(vec![(i, 1)]),
+                    None => Err(PresentationError::UnknownGenerator(name)),
+                }
+            }
+            _ => self.malformed("expected a generator, '1' or '('"),
+        }
+    }
+}
+
+/// Parses a word like "f^2 g^-1" or "(f g)^3" over the named generators. "lhs = rhs" is read as the relator
+/// lhs * rhs^-1, so "f^2 = g^3" and "f^2 g^-3" are the same thing.
+pub fn parse_word(s: &str, generators: &[&str]) -> Result<Word, PresentationError> {
+    let mut parser = WordParser {
+        chars: s.chars().collect(),
+        position: 0,
+        generators,
+    };
+    let mut word = parser.word()?;
+    if parser.chars.get(parser.position) == Some(&'=') {
+        parser.position += 1;
+        word.extend(invert_word(&parser.word()?));
+    }
+    match parser.chars.get(parser.position) {
+        None => Ok(word),
+        Some(_) => parser.malformed("unexpected character"),
+    }
+}
+
+/// Columns of the coset table: 2i is generator i and 2i + 1 its inverse.
+fn columns(word: &[(usize, i64)]) -> Vec<usize> {
+    word.iter()
+        .flat_map(|&(g, e)| {
+            let column = if e < 0 { 2 * g + 1 } else { 2 * g };
+            std::iter::repeat_n(column, e.unsigned_abs() as usize)
+        })
+        .collect()
+}
+
+/// Haselgrove-Leech-Trotter style Todd-Coxeter enumeration, following Holt's Handbook of Computational Group Theory.
+/// Cosets are rows, and a coset that turned out to equal a smaller one points at it through `parent`.
+struct CosetTable {
+    table: Vec<Vec<Option<usize>>>,
+    parent: Vec<usize>,
+    max_cosets: usize,
+}
+
+impl CosetTable {
+    fn new(generators: usize, max_cosets: usize) -> CosetTable {
+        CosetTable {
+            table: vec![vec![None; 2 * generators]],
+            parent: vec![0],
+            max_cosets,
+        }
+    }
+
+    fn is_live(&self, c: usize) -> bool {
+        self.parent[c] == c
+    }
+
+    fn define(&mut self, c: usize, x: usize) -> Result<(), PresentationError> {
+        if self.table.len() >= self.max_cosets {
+            return Err(PresentationError::TooManyCosets(self.table.len()));
+        }
+        let d = self.table.len();
+        self.table.push(vec![None; self.table[0].len()]);
+        self.parent.push(d);
+        self.table[c][x] = Some(d);
+        self.table[d][x ^ 1] = Some(c);
+        Ok(())
+    }
+
+    fn rep(&mut self, c: usize) -> usize {
+        let mut root = c;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut c = c;
+        while self.parent[c] != root {
+            let next = self.parent[c];
+            self.parent[c] = root;
+            c = next;
+        }
+        root
+    }
+
+    fn merge(&mut self, a: usize, b: usize, queue: &mut Vec<usize>) {
+        let (a, b) = (self.rep(a), self.rep(b));
+        if a != b {
+            let (keep, drop) = (a.min(b), a.max(b));
+            self.parent[drop] = keep;
+            queue.push(drop);
+        }
+    }
+
+    /// Identifies two cosets, and then everything that forces.
+    fn coincidence(&mut self, a: usize, b: usize) {
+        let mut queue = Vec::new();
+        self.merge(a, b, &mut queue);
+        let mut i = 0;
+        while i < queue.len() {
+            let dead = queue[i];
+            i += 1;
+            for x in 0..self.table[dead].len() {
+                let Some(d) = self.table[dead][x] else {
+                    continue;
+                };
+                self.table[d][x ^ 1] = None;
+                let (mu, nu) = (self.rep(dead), self.rep(d));
+                if let Some(e) = self.table[mu][x] {
+                    self.merge(nu, e, &mut queue);
+                } else if let Some(e) = self.table[nu][x ^ 1] {
+                    self.merge(mu, e, &mut queue);
+                } else {
+                    self.table[mu][x] = Some(nu);
+                    self.table[nu][x ^ 1] = Some(mu);
+                }
+            }
+        }
+    }
+
+    /// Traces the word from c forwards and backwards, defining cosets until it closes up.
+    fn scan_and_fill(&mut self, c: usize, word: &[usize]) -> Result<(), PresentationError> {
+        let (mut f, mut b) = (c, c);
+        let (mut i, mut j) = (0, word.len());
+        loop {
+            while i < j {
+                match self.table[f][word[i]] {
+                    Some(next) => {
+                        f = next;
+                        i += 1;
+                    }
+                    None => break,
+                }
+            }
+            if i == j {
+                if f != b {
+                    self.coincidence(f, b);
+                }
+                return Ok(());
+            }
+            while j > i {
+                match self.table[b][word[j - 1] ^ 1] {
+                    Some(next) => {
+                        b = next;
+                        j -= 1;
+                    }
+                    None => break,
+                }
+            }
+            if j == i {
+                self.coincidence(f, b);
+                return Ok(());
+            }
+            if j == i + 1 {
+                // a deduction, the gap is exactly one letter
+                self.table[f][word[i]] = Some(b);
+                self.table[b][word[i] ^ 1] = Some(f);
+                return Ok(());
+            }
+            self.define(f, word[i])?;
+        }
+    }
+
+    fn enumerate(&mut self, relators: &[Vec<usize>]) -> Result<(), PresentationError> {
+        let mut c = 0;
+        while c < self.table.len() {
+            for relator in relators.iter() {
+                if !self.is_live(c) {
+                    break;
+                }
+                self.scan_and_fill(c, relator)?;
+            }
+            if self.is_live(c) {
+                for x in 0..self.table[c].len() {
+                    if self.table[c][x].is_none() {
+                        self.define(c, x)?;
+                    }
+                }
+            }
+            c += 1;
+        }
+        Ok(())
+    }
+}
+
+/// A group given by generators and relators, ie <f, g | f^2, g^3, (f g)^3>, turned into permutations by Todd-Coxeter
+/// coset enumeration. The ground set is the cosets 1..=m of a subgroup (the trivial one unless given), coset 1 being
+/// the subgroup itself, and each generator becomes the Cycle it induces on them. On the trivial subgroup this is the
+/// regular representation, so m is the order of the group.
+pub struct FinitelyPresentedGroup {
+    names: Vec<String>,
+    relators: Vec<Word>,
+    ground: GroundSet<usize>,
+    group: PermutationGroup<usize>,
+}
+
+impl FinitelyPresentedGroup {
+    /// The group acting on itself. Fails with TooManyCosets on infinite groups, or past DEFAULT_MAX_COSETS.
+    pub fn new(generators: &[&str], relators: &[Word]) -> Result<Self, PresentationError> {
+        FinitelyPresentedGroup::on_cosets(generators, relators, &[], DEFAULT_MAX_COSETS)
+    }
+
+    /// Like new, with the relators written out, ie parse(&["f", "g"], &["f^2", "g^3", "(f g)^3"]).
+    pub fn parse(generators: &[&str], relators: &[&str]) -> Result<Self, PresentationError> {
+        let relators = relators
+            .iter()
+            .map(|r| parse_word(r, generators))
+            .collect::<Result<Vec<Word>, PresentationError>>()?;
+        FinitelyPresentedGroup::new(generators, &relators)
+    }
+
+    /// The group acting on the cosets of the subgroup generated by the given words, which gives smaller
+    /// permutations than the regular representation. They are only faithful when the subgroup contains no
+    /// normal subgroup, eg S_n on the cosets of S_(n-1) is the usual action on n points.
+    pub fn on_cosets(
+        generators: &[&str],
+        relators: &[Word],
+        subgroup: &[Word],
+        max_cosets: usize,
+    ) -> Result<Self, PresentationError> {
+        for &(g, _) in relators.iter().chain(subgroup.iter()).flatten() {
+            if g >= generators.len() {
+                return Err(PresentationError::UnknownGenerator(g.to_string()));
+            }
+        }
+        let mut table = CosetTable::new(generators.len(), max_cosets.max(1));
+        for word in subgroup.iter() {
+            table.scan_and_fill(0, &columns(word))?;
+        }
+        let relators_columns: Vec<Vec<usize>> = relators.iter().map(|r| columns(r)).collect();
+        table.enumerate(&relators_columns)?;
+
+        let live: Vec<usize> = (0..table.table.len())
+            .filter(|&c| table.is_live(c))
+            .collect();
+        let mut number = vec![0u32; table.table.len()];
+        for (i, &c) in live.iter().enumerate() {
+            number[c] = i as u32;
+        }
+        let ground = GroundSet::new((1..=live.len()).collect());
+        let cycles: Vec<Cycle<usize>> = (0..generators.len())
+            .map(|g| {
+                // right cosets H x, so x moves H y to H y x
+                let image = live
+                    .iter()
+                    .map(|&c| {
+                        let d = table.table[c][2 * g].expect("coset enumeration left a gap");
+                        number[table.rep(d)]
+                    })
+                    .collect();
+                Cycle::from_images(image, &ground)
+            })
+            .collect();
+        Ok(FinitelyPresentedGroup {
+            names: generators.iter().map(|s| s.to_string()).collect(),
+            relators: relators.to_vec(),
+            group: PermutationGroup::new(cycles, &ground),
+            ground,
+        })
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn relators(&self) -> &[Word] {
+        &self.relators
+    }
+
+    pub fn ground_set(&self) -> &GroundSet<usize> {
+        &self.ground
+    }
+
+    /// How many cosets there are, ie the size of the permutations.
+    pub fn degree(&self) -> usize {
+        self.ground.len()
+    }
+
+    /// The permutation of a named generator.
+    pub fn generator(&self, name: &str) -> Option<Cycle<usize>> {
+        let i = self.names.iter().position(|n| n == name)?;
+        Some(self.group.get_generator()[i].clone())
+    }
+
+    /// The permutation of a word. Since cosets are acted on from the right, the letters are applied left to right,
+    /// so the word f g is the Cycle g * f.
+    pub fn evaluate(&self, word: &[(usize, i64)]) -> Cycle<usize> {
+        let generators = self.group.get_generator();
+        word.iter()
+            .fold(self.identity(), |acc, &(g, e)| &generators[g].pow(e) * &acc)
+    }
+
+    pub fn contains(&self, cycle: &Cycle<usize>) -> bool {
+        self.group.contains(cycle)
+    }
+}
+
+impl Group<Cycle<usize>> for FinitelyPresentedGroup {
+    fn elements(&self) -> Box<dyn Iterator<Item = Cycle<usize>> + Send + '_> {
+        self.group.elements()
+    }
+
+    fn elements_range(
+        &self,
+        positions: Range<u128>,
+    ) -> Box<dyn Iterator<Item = Cycle<usize>> + Send + '_> {
+        self.group.elements_range(positions)
+    }
+
+    fn seekable(&self) -> bool {
+        self.group.seekable()
+    }
+
+    fn op(&self, a: Cycle<usize>, b: Cycle<usize>) -> Cycle<usize> {
+        a * b
+    }
+
+    fn identity(&self) -> Cycle<usize> {
+        Cycle::identity(&self.ground)
+    }
+
+    fn inverse(&self, e: Cycle<usize>) -> Cycle<usize> {
+        e.inverse()
+    }
+
+    fn order(&self) -> u128 {
+        self.group.order()
+    }
+
+    fn get_generator(&self) -> Vec<Cycle<usize>> {
+        self.group.get_generator()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::gen::Generator;
+    use crate::generator::periodic::PeriodicGen;
+    use crate::graph::cayley_graph::CayleyGraph;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::presentation::{
+        parse_word, FinitelyPresentedGroup, PresentationError,
+    };
+
+    #[test]
+    fn parse_words() {
+        let gens = ["f", "g"];
+        debug_assert_eq!(
+            parse_word("f^2 g^-1", &gens),
+            Ok(vec![(0, 1), (0, 1), (1, -1)])
+        );
+        debug_assert_eq!(
+            parse_word("(f g)^2", &gens),
+            Ok(vec![(0, 1), (1, 1), (0, 1), (1, 1)])
+        );
+        debug_assert_eq!(parse_word("(f*g)^-1", &gens), Ok(vec![(1, -1), (0, -1)]));
+        debug_assert_eq!(parse_word("f = g", &gens), Ok(vec![(0, 1), (1, -1)]));
+        debug_assert_eq!(parse_word("1", &gens), Ok(vec![]));
+        debug_assert_eq!(
+            parse_word("f h", &gens),
+            Err(PresentationError::UnknownGenerator("h".to_string()))
+        );
+        debug_assert!(matches!(
+            parse_word("(f g", &gens),
+            Err(PresentationError::Malformed { .. })
+        ));
+        debug_assert!(matches!(
+            parse_word("f^", &gens),
+            Err(PresentationError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn small_groups() {
+        // S_3: two passes of f are the identity, and f then g has order 3
+        let s_3 = FinitelyPresentedGroup::parse(&["f", "g"], &["f^2", "g^2", "(f g)^3"]).unwrap();
+        debug_assert_eq!(s_3.order(), 6);
+        debug_assert_eq!(s_3.degree(), 6);
+        let a_4 = FinitelyPresentedGroup::parse(&["a", "b"], &["a^2", "b^3", "(a b)^3"]).unwrap();
+        debug_assert_eq!(a_4.order(), 12);
+        let q_8 =
+            FinitelyPresentedGroup::parse(&["i", "j"], &["i^4", "i^2 = j^2", "j^-1 i j = i^-1"])
+                .unwrap();
+        debug_assert_eq!(q_8.order(), 8);
+        let d_6 = FinitelyPresentedGroup::parse(&["r", "s"], &["r^6", "s^2", "(s r)^2"]).unwrap();
+        debug_assert_eq!(d_6.order(), 12);
+        let trivial = FinitelyPresentedGroup::parse(&["a"], &["a", "a^2"]).unwrap();
+        debug_assert_eq!(trivial.order(), 1);
+    }
+
+    #[test]
+    fn relators_hold() {
+        let gens = ["f", "g"];
+        let relators = ["f^2", "g^2", "(f g)^4"];
+        let d_4 = FinitelyPresentedGroup::parse(&gens, &relators).unwrap();
+        for r in relators.iter() {
+            let word = parse_word(r, &gens).unwrap();
+            debug_assert_eq!(d_4.evaluate(&word), d_4.identity());
+        }
+        let fg = d_4.evaluate(&parse_word("f g", &gens).unwrap());
+        debug_assert_eq!(fg.order(), 4);
+        debug_assert_eq!(
+            fg,
+            &d_4.generator("g").unwrap() * &d_4.generator("f").unwrap()
+        );
+        debug_assert!(d_4.contains(&fg));
+    }
+
+    #[test]
+    fn cosets_of_a_subgroup() {
+        // S_4 = <s_1, s_2, s_3 | Coxeter relations> on the cosets of S_3 = <s_1, s_2> is S_4 on 4 points
+        let gens = ["a", "b", "c"];
+        let relators: Vec<_> = ["a^2", "b^2", "c^2", "(a b)^3", "(b c)^3", "(a c)^2"]
+            .iter()
+            .map(|r| parse_word(r, &gens).unwrap())
+            .collect();
+        let subgroup = vec![
+            parse_word("a", &gens).unwrap(),
+            parse_word("b", &gens).unwrap(),
+        ];
+        let s_4 = FinitelyPresentedGroup::on_cosets(&gens, &relators, &subgroup, 1000).unwrap();
+        debug_assert_eq!(s_4.degree(), 4);
+        debug_assert_eq!(s_4.order(), 24);
+        let regular = FinitelyPresentedGroup::new(&gens, &relators).unwrap();
+        debug_assert_eq!(regular.degree(), 24);
+    }
+
+    #[test]
+    fn infinite_groups_give_up() {
+        let z = FinitelyPresentedGroup::on_cosets(&["a"], &[], &[], 100);
+        debug_assert_eq!(z.err(), Some(PresentationError::TooManyCosets(100)));
+        let z_2 =
+            FinitelyPresentedGroup::on_cosets(&["a", "b"], &[vec![(0, 2)], vec![(1, 2)]], &[], 100);
+        debug_assert!(matches!(z_2, Err(PresentationError::TooManyCosets(_))));
+        debug_assert_eq!(
+            FinitelyPresentedGroup::new(&["a"], &[vec![(1, 2)]]).err(),
+            Some(PresentationError::UnknownGenerator("1".to_string()))
+        );
+    }
+
+    #[test]
+    fn drives_the_machinery() {
+        let s_3 = FinitelyPresentedGroup::parse(&["f", "g"], &["f^2", "g^2", "(f g)^3"]).unwrap();
+        let mut graph: CayleyGraph<usize> = CayleyGraph::new(&s_3);
+        graph.apply(&s_3.identity().get_ground());
+        let f = s_3.generator("f").unwrap();
+        debug_assert_eq!(
+            graph.get_values(f.clone()),
+            Some(&(1..=6).map(|x| f.eval(x)).collect::<Vec<usize>>())
+        );
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&s_3.identity().get_ground());
+        generator.add(f.get_function());
+        let passes: Vec<Vec<usize>> = generator.iter().take(3).collect();
+        debug_assert_eq!(passes[0], passes[2]);
+        debug_assert_ne!(passes[0], passes[1]);
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+use crate::group_theory::group::Group;
+use crate::group_theory::permutation_group::PermutationGroup;
+use crate::group_theory::symmetric::sym;
+
+/// The images of g moved up to positions offset.., as a permutation of 0..total.
+fn shifted<V>(g: &Cycle<V>, offset: usize, total: usize) -> Vec<u32>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    let mut image: Vec<u32> = (0..total as u32).collect();
+    for (i, &j) in g.images().iter().enumerate() {
+        image[offset + i] = offset as u32 + j;
+    }
+    image
+}
+
+/// Moves whole blocks of the given size around like g moves points: block i lands where block g(i) is.
+fn blockwise<V>(g: &Cycle<V>, block: usize) -> Vec<u32>
+where
+    V: Clone + Hash + Eq + Debug + 'static,
+{
+    g.images()
+        .iter()
+        .flat_map(|&j| (0..block as u32).map(move |p| j * block as u32 + p))
+        .collect()
+}
+
+/// Products of groups of cycles. The direct and wreath products act on 1..=N, with the ground set of each factor
+/// replaced by its positions, so they can be listed and plotted like sym(N). Each returns a PermutationGroup, which
+/// knows its order and can test membership.
+pub trait Products<V>: Group<Cycle<V>>
+where
+    V: Clone + Hash + Eq + Debug + Send + Sync + 'static,
+{
+    /// G x H acting on 1..=n+m, G on the first n points and H on the last m.
+    fn direct_product<W, H>(&self, other: &H) -> PermutationGroup<usize>
+    where
+        W: Clone + Hash + Eq + Debug + Send + Sync + 'static,
+        H: Group<Cycle<W>> + ?Sized,
+    {
+        let (n, m) = (self.identity().n(), other.identity().n());
+        let ground: Vec<usize> = (1..=n + m).collect();
+        let generators = self
+            .get_generator()
+            .iter()
+            .map(|g| shifted(g, 0, n + m))
+            .chain(other.get_generator().iter().map(|h| shifted(h, n, n + m)))
+            .map(|image| Cycle::from_images(image, &ground))
+            .collect();
+        PermutationGroup::new(generators, &ground)
+    }
+
+    /// G wr H, for G acting on b points and H on k: 1..=b*k is cut into k consecutive blocks of b, G acts inside
+    /// every block independently and H moves the blocks around. This is what a tiled loop can do to a trace,
+    /// reorder within tiles and reorder the tiles. The order is |G|^k * |H|.
+    fn wreath_product<W, H>(&self, top: &H) -> PermutationGroup<usize>
+    where
+        W: Clone + Hash + Eq + Debug + Send + Sync + 'static,
+        H: Group<Cycle<W>> + ?Sized,
+    {
+        let (b, k) = (self.identity().n(), top.identity().n());
+        let ground: Vec<usize> = (1..=b * k).collect();
+        let base = self.get_generator();
+        let generators = (0..k)
+            .flat_map(|block| base.iter().map(move |g| shifted(g, block * b, b * k)))
+            .chain(top.get_generator().iter().map(|h| blockwise(h, b)))
+            .map(|image| Cycle::from_images(image, &ground))
+            .collect();
+        PermutationGroup::new(generators, &ground)
+    }
+
+    /// N x| H, for self = N normalized by the complement H on the same ground set, with only the identity in common.
+    /// Panics when H doesn't normalize N or they overlap, since then the elements don't form N x| H.
+    fn semidirect_product<H>(&self, complement: &H) -> PermutationGroup<V>
+    where
+        H: Group<Cycle<V>> + ?Sized,
+    {
+        let ground = self.identity().ground_set().clone();
+        let normal = PermutationGroup::new(self.get_generator(), &ground);
+        let complement = PermutationGroup::new(complement.get_generator(), &ground);
+        for h in complement.get_generator() {
+            for g in normal.get_generator() {
+                assert!(
+                    normal.contains(&g.conjugate_by(&h)),
+                    "{:?} doesn't normalize the group",
+                    h.get_cycle_representation(false)
+                );
+            }
+        }
+        let generators = normal
+            .get_generator()
+            .into_iter()
+            .chain(complement.get_generator())
+            .collect();
+        let product = PermutationGroup::new(generators, &ground);
+        assert_eq!(
+            product.order(),
+            normal.order() * complement.order(),
+            "the complement meets the normal subgroup"
+        );
+        product
+    }
+}
+
+impl<V, G> Products<V> for G
+where
+    G: Group<Cycle<V>> + ?Sized,
+    V: Clone + Hash + Eq + Debug + Send + Sync + 'static,
+{
+}
+
+/// The hyperoctahedral group B_k of signed permutations, S_2 wr S_k acting on 1..=2k: the pairs (2i - 1, 2i) stand
+/// for +i and -i, a permutation of the pairs permutes the i and a swap inside one flips its sign. The order is 2^k * k!.
+pub fn hyperoctahedral(k: usize) -> PermutationGroup<usize> {
+    sym(2).wreath_product(&sym(k))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::generator::gen::Generator;
+    use crate::generator::periodic::PeriodicGen;
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::cyclic::cyclic;
+    use crate::group_theory::dihedral::dihedral;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::permutation_group::PermutationGroup;
+    use crate::group_theory::product::{hyperoctahedral, Products};
+    use crate::group_theory::symmetric::sym;
+
+    #[test]
+    fn direct_products() {
+        let product = sym(3).direct_product(&cyclic(4));
+        debug_assert_eq!(product.order(), 24);
+        debug_assert_eq!(product.identity().n(), 7);
+        // nothing crosses between the factors
+        for g in product.elements() {
+            debug_assert!((0..3).all(|i| g.images()[i] < 3));
+        }
+        let ground: Vec<usize> = (1..=7).collect();
+        let g = Cycle::from(vec![vec![1, 2], vec![4, 5, 6, 7]], &ground);
+        debug_assert!(product.contains(&g));
+        debug_assert!(!product.contains(&Cycle::from(vec![vec![3, 4]], &ground)));
+    }
+
+    #[test]
+    fn wreath_products() {
+        // S_3 wr S_2: 6^2 * 2
+        let wreath = sym(3).wreath_product(&sym(2));
+        debug_assert_eq!(wreath.order(), 72);
+        let ground: Vec<usize> = (1..=6).collect();
+        let swap_blocks = Cycle::from(vec![vec![1, 4], vec![2, 5], vec![3, 6]], &ground);
+        debug_assert!(wreath.contains(&swap_blocks));
+        debug_assert!(!wreath.contains(&Cycle::from(vec![vec![3, 4]], &ground)));
+        // every element maps blocks onto blocks
+        let blocks: HashSet<Vec<u32>> = HashSet::from([vec![0, 1, 2], vec![3, 4, 5]]);
+        for g in wreath.elements() {
+            for block in blocks.iter() {
+                let mut image: Vec<u32> = block.iter().map(|&i| g.images()[i as usize]).collect();
+                image.sort();
+                debug_assert!(blocks.contains(&image));
+            }
+        }
+        debug_assert_eq!(
+            dihedral(4).wreath_product(&cyclic(3)).order(),
+            8 * 8 * 8 * 3
+        );
+    }
+
+    #[test]
+    fn signed_permutations() {
+        for k in 1..=4 {
+            let b_k = hyperoctahedral(k);
+            debug_assert_eq!(
+                b_k.order(),
+                (1u128 << k) * (1..=k as u128).product::<u128>()
+            );
+        }
+        // a tiled trace keeps its tiles together pass after pass
+        let b_3 = hyperoctahedral(3);
+        let g = Cycle::from(vec![vec![1, 4, 2, 3], vec![5, 6]], b_3.ground_set());
+        debug_assert!(b_3.contains(&g));
+        let mut generator = PeriodicGen::new();
+        generator.set_start(&b_3.identity().get_ground());
+        generator.add(g.get_function());
+        for pass in generator.iter().take(5) {
+            for tile in pass.chunks(2) {
+                debug_assert_eq!(tile[0].div_ceil(2), tile[1].div_ceil(2));
+            }
+        }
+    }
+
+    #[test]
+    fn semidirect_products() {
+        let ground: Vec<usize> = (1..=4).collect();
+        let rotations = cyclic(4);
+        let flip = PermutationGroup::new(vec![Cycle::from(vec![vec![2, 4]], &ground)], &ground);
+        // C_4 x| C_2 is D_4
+        let d_4 = rotations.semidirect_product(&flip);
+        debug_assert_eq!(d_4.order(), 8);
+        debug_assert_eq!(d_4.get_set(), dihedral(4).get_set());
+    }
+
+    #[test]
+    #[should_panic]
+    fn semidirect_needs_normal() {
+        let ground: Vec<usize> = (1..=3).collect();
+        let s_3 = sym(3);
+        let transposition =
+            PermutationGroup::new(vec![Cycle::from(vec![vec![1, 2]], &ground)], &ground);
+        transposition.semidirect_product(&s_3);
+    }
+}
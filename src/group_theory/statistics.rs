@@ -0,0 +1,212 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::Cycle;
+
+fn gcd(a: u128, b: u128) -> u128 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// The length of the longest strictly increasing subsequence, by patience sorting.
+fn longest_increasing(values: impl Iterator<Item = u32>) -> usize {
+    let mut piles: Vec<u32> = Vec::new();
+    for v in values {
+        match piles.binary_search(&v) {
+            Ok(_) => {}
+            Err(i) if i == piles.len() => piles.push(v),
+            Err(i) => piles[i] = v,
+        }
+    }
+    piles.len()
+}
+
+/// The classical permutation statistics.
+/// Like inversions, these read the permutation in one-line notation, w(1) w(2) ... w(n), and compare
+/// both positions and values by their order in the ground set. Positions are 1-indexed, as is standard.
+impl<T> Cycle<T>
+where
+    T: Debug + Clone + Eq + Hash + 'static,
+{
+    /// +1 for even permutations and -1 for odd ones.
+    pub fn sign(&self) -> i8 {
+        match self.is_even() {
+            true => 1,
+            false => -1,
+        }
+    }
+
+    /// A permutation is even when it is a product of an even number of transpositions, ie n minus its number of cycles is even.
+    pub fn is_even(&self) -> bool {
+        (self.n() - self.num_cycles()).is_multiple_of(2)
+    }
+
+    /// The smallest k > 0 with w^k = e, which is the lcm of the cycle lengths.
+    /// A PeriodicGen trace driven only by w repeats with exactly this period.
+    /// Panics if it doesn't fit in a u128, which takes n in the thousands.
+    pub fn order(&self) -> u128 {
+        self.checked_order().unwrap_or_else(|| {
+            panic!(
+                "the order of a permutation of {} doesn't fit in a u128",
+                self.n()
+            )
+        })
+    }
+
+    /// Like order, but None if it doesn't fit in a u128.
+    pub fn checked_order(&self) -> Option<u128> {
+        self.get_index_cycles(false)
+            .iter()
+            .map(|cycle| cycle.len() as u128)
+            .try_fold(1u128, |acc, len| (acc / gcd(acc, len)).checked_mul(len))
+    }
+
+    /// The cycle lengths from largest to smallest (including the 1-cycles), which is a partition of n.
+    pub fn cycle_type(&self) -> Vec<usize> {
+        let mut lengths: Vec<usize> = self
+            .get_index_cycles(true)
+            .iter()
+            .map(|cycle| cycle.len())
+            .collect();
+        lengths.sort_unstable_by(|a, b| b.cmp(a));
+        lengths
+    }
+
+    /// The number of cycles, counting fixed points as 1-cycles.
+    pub fn num_cycles(&self) -> usize {
+        self.get_index_cycles(true).len()
+    }
+
+    pub fn fixed_points(&self) -> usize {
+        self.images()
+            .iter()
+            .enumerate()
+            .filter(|(i, &w)| *i as u32 == w)
+            .count()
+    }
+
+    /// The positions i with w(i) > w(i + 1).
+    pub fn descent_set(&self) -> Vec<usize> {
+        self.images()
+            .windows(2)
+            .enumerate()
+            .filter(|(_, w)| w[0] > w[1])
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+
+    pub fn descents(&self) -> usize {
+        self.descent_set().len()
+    }
+
+    /// The sum of the descent positions.
+    pub fn major_index(&self) -> usize {
+        self.descent_set().iter().sum()
+    }
+
+    /// The number of positions i with w(i) > i.
+    pub fn excedances(&self) -> usize {
+        self.images()
+            .iter()
+            .enumerate()
+            .filter(|(i, &w)| w > *i as u32)
+            .count()
+    }
+
+    pub fn longest_increasing_subsequence(&self) -> usize {
+        longest_increasing(self.images().iter().copied())
+    }
+
+    pub fn longest_decreasing_subsequence(&self) -> usize {
+        longest_increasing(self.images().iter().map(|&w| u32::MAX - w))
+    }
+
+    /// The number of positions 1 < i < n with w(i - 1) < w(i) > w(i + 1).
+    pub fn peaks(&self) -> usize {
+        self.images()
+            .windows(3)
+            .filter(|w| w[0] < w[1] && w[1] > w[2])
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::group::Group;
+    use crate::group_theory::symmetric::sym;
+
+    #[test]
+    fn statistics_31524() {
+        let ground = vec![1, 2, 3, 4, 5];
+        let w = Cycle::from_retraversal(&[3, 1, 5, 2, 4], ground);
+        debug_assert_eq!(w.descent_set(), vec![1, 3]);
+        debug_assert_eq!(w.descents(), 2);
+        debug_assert_eq!(w.major_index(), 4);
+        debug_assert_eq!(w.excedances(), 2);
+        debug_assert_eq!(w.fixed_points(), 0);
+        debug_assert_eq!(w.peaks(), 1);
+        debug_assert_eq!(w.longest_increasing_subsequence(), 3);
+        debug_assert_eq!(w.longest_decreasing_subsequence(), 2);
+        // (1,3,5,4,2)
+        debug_assert_eq!(w.cycle_type(), vec![5]);
+        debug_assert_eq!(w.num_cycles(), 1);
+        debug_assert_eq!(w.order(), 5);
+        debug_assert_eq!(w.sign(), 1);
+    }
+
+    #[test]
+    fn statistics_cycles() {
+        let ground = vec![1, 2, 3, 4, 5, 6];
+        let w = Cycle::from(vec![vec![1, 2], vec![3, 4, 5]], ground);
+        debug_assert_eq!(w.cycle_type(), vec![3, 2, 1]);
+        debug_assert_eq!(w.num_cycles(), 3);
+        debug_assert_eq!(w.fixed_points(), 1);
+        debug_assert_eq!(w.order(), 6);
+        debug_assert_eq!(w.sign(), -1);
+        debug_assert!(!w.is_even());
+    }
+
+    #[test]
+    fn order_past_u128() {
+        // one cycle for every prime up to 107, whose product is above 2^128
+        let primes: Vec<usize> = (2..=107)
+            .filter(|&p: &usize| (2..p).all(|d| p % d != 0))
+            .collect();
+        let mut cycles = Vec::new();
+        let mut next = 1;
+        for p in primes {
+            cycles.push((next..next + p).collect::<Vec<usize>>());
+            next += p;
+        }
+        let w = Cycle::from(cycles, (1..next).collect::<Vec<usize>>());
+        debug_assert_eq!(w.checked_order(), None);
+        debug_assert_eq!(w.pow(1), w);
+        debug_assert_eq!(w.pow(-1), w.inverse());
+        debug_assert_eq!(w.pow(2 * 3 * 5 * 7).cycle_type()[..4], [107, 103, 101, 97]);
+    }
+
+    #[test]
+    fn sign_is_parity_of_inversions() {
+        let s_5 = sym(5);
+        for w in s_5.get_set() {
+            debug_assert_eq!(w.is_even(), w.inversions() % 2 == 0);
+            debug_assert_eq!(w.sign(), w.inverse().sign());
+        }
+    }
+
+    #[test]
+    fn major_index_equidistributed() {
+        // MacMahon: inversions and major index have the same distribution over S_n
+        let s_5 = sym(5);
+        let mut inv = vec![0; 11];
+        let mut maj = vec![0; 11];
+        for w in s_5.get_set() {
+            inv[w.inversions()] += 1;
+            maj[w.major_index()] += 1;
+        }
+        debug_assert_eq!(inv, maj);
+    }
+}
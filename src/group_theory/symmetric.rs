@@ -1,47 +1,124 @@
-use bimap::{BiHashMap, BiMap};
-use permutohedron::heap_recursive;
-use std::collections::HashSet;
+use bimap::BiHashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::ops::Range;
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use crate::group_theory::cycle::Cycle;
+use crate::group_theory::enumeration::{EnumerationOrder, Permutations};
+use crate::group_theory::generating::GeneratingSet;
+use crate::group_theory::ground::GroundSet;
 use crate::group_theory::group::Group;
+use crate::math::combinations::{checked_factorial, from_factoradic, to_factoradic};
 
 pub fn sym(n_size: usize) -> SymmetricGroup<usize> {
-    SymmetricGroup {
-        n: n_size,
-        ground: (1..=n_size).collect(),
-    }
+    SymmetricGroup::new(n_size, (1..=n_size).collect())
 }
 
 #[derive(Default)]
-pub struct SymmetricGroup<V> {
+pub struct SymmetricGroup<V>
+where
+    V: Clone + Hash + Eq + 'static,
+{
     /// the base size of a permutation the order should be n!
     n: usize,
-    ground: Vec<V>,
+    /// Shared by every element of the group, so cycles made here never copy the ground set.
+    ground: GroundSet<V>,
+    /// The order elements() lists the group in.
+    enumeration: EnumerationOrder,
+    /// What get_generator gives.
+    generating_set: GeneratingSet<V>,
 }
 impl<V> SymmetricGroup<V>
 where
-    V: Clone + Copy + Hash + Eq + PartialEq + Debug,
+    V: Clone + Copy + Hash + Eq + PartialEq + Debug + 'static,
 {
     pub fn new(n_size: usize, g: Vec<V>) -> SymmetricGroup<V> {
         SymmetricGroup {
             n: n_size,
-            ground: g,
+            ground: GroundSet::new(g),
+            enumeration: EnumerationOrder::default(),
+            generating_set: GeneratingSet::default(),
+        }
+    }
+
+    /// Lists the elements in another order, eg a Gray code where neighbours differ by one swap.
+    pub fn with_enumeration(mut self, enumeration: EnumerationOrder) -> SymmetricGroup<V> {
+        self.enumeration = enumeration;
+        self
+    }
+
+    pub fn enumeration(&self) -> EnumerationOrder {
+        self.enumeration
+    }
+
+    /// Uses other generators than the adjacent transpositions. Panics if custom generators aren't over the ground set.
+    pub fn with_generating_set(mut self, generating_set: GeneratingSet<V>) -> SymmetricGroup<V> {
+        if let GeneratingSet::Custom(generators) = &generating_set {
+            assert!(
+                generators.iter().all(|g| g.ground_set() == &self.ground),
+                "generators have to be over the ground set of the group"
+            );
+        }
+        self.generating_set = generating_set;
+        self
+    }
+
+    pub fn generating_set(&self) -> &GeneratingSet<V> {
+        &self.generating_set
+    }
+
+    /// The length of w in the generating set, when there is a formula for it (see GeneratingSet::length).
+    pub fn length(&self, w: &Cycle<V>) -> Option<usize> {
+        self.generating_set.length(w)
+    }
+
+    /// The largest length in the generating set, when there is a formula for it.
+    pub fn diameter(&self) -> Option<usize> {
+        self.generating_set.diameter(self.ground.len())
+    }
+
+    /// The length of every permutation in the generating set, found by a BFS over the Cayley graph from the identity.
+    /// This works for any generating set, but holds all of S_n.
+    pub fn distances(&self) -> HashMap<Cycle<V>, usize> {
+        let generators = self.generating_set.generators(&self.ground);
+        let mut distances = HashMap::from([(Cycle::identity(&self.ground), 0)]);
+        let mut q = VecDeque::from([Cycle::identity(&self.ground)]);
+        while let Some(w) = q.pop_front() {
+            let d = distances[&w];
+            for g in generators.iter() {
+                let next = &w * g;
+                if !distances.contains_key(&next) {
+                    distances.insert(next.clone(), d + 1);
+                    q.push_back(next);
+                }
+            }
         }
+        distances
+    }
+
+    /// Cuts elements() into at most `chunks` contiguous pieces that can be listed independently, eg by rayon workers.
+    pub fn chunks(&self, chunks: usize) -> Vec<Permutations<V>> {
+        Permutations::chunks(&self.ground, self.enumeration, chunks)
     }
 
     pub fn get_ground(&self) -> Vec<V> {
-        self.ground.clone()
+        self.ground.to_vec()
+    }
+
+    pub fn ground_set(&self) -> &GroundSet<V> {
+        &self.ground
     }
 
     ///Shorthand for making new cycles with respect to a symmetric group.
     pub fn create(&self, map: BiHashMap<V, V>) -> Cycle<V> {
-        Cycle::new(map, self.get_ground())
+        Cycle::new(map, &self.ground)
     }
 
     pub fn create_vec(&self, lis: Vec<Vec<V>>) -> Cycle<V> {
-        Cycle::from(lis, self.get_ground())
+        Cycle::from(lis, &self.ground)
     }
 
     pub fn create_retraversal(&self, retraversal: &[V]) -> Cycle<V> {
@@ -51,19 +128,63 @@ where
     pub fn ground_size(&self) -> usize {
         self.ground.len()
     }
+
+    /// The position of the permutation when S_n is listed in lexicographic order of one-line notation,
+    /// so the identity is 0 and the reversal is n! - 1. This is the Lehmer code read as a factoradic number.
+    /// Panics if n! doesn't fit in a u128 (n > 34).
+    pub fn rank(&self, cycle: &Cycle<V>) -> u128 {
+        from_factoradic(&cycle.lehmer_code())
+            .unwrap_or_else(|| panic!("ranks of S_{} don't fit in a u128", self.n))
+    }
+
+    /// The permutation with the given lexicographic rank. Panics if k >= n!.
+    pub fn unrank(&self, k: u128) -> Cycle<V> {
+        self.try_unrank(k)
+            .unwrap_or_else(|| panic!("{} is not a rank of S_{}", k, self.n))
+    }
+
+    /// Like unrank, but None if k >= n!.
+    pub fn try_unrank(&self, k: u128) -> Option<Cycle<V>> {
+        let code = to_factoradic(k, self.ground.len())?;
+        Some(Cycle::from_lehmer_code(&code, &self.ground).unwrap())
+    }
+
+    /// The permutations with ranks in the range, in lexicographic order.
+    /// Handy for splitting S_n into shards that can be worked on independently.
+    pub fn ranked(&self, ranks: Range<u128>) -> impl Iterator<Item = Cycle<V>> + '_ {
+        ranks.map(|k| self.unrank(k))
+    }
 }
 
 impl<V> Group<Cycle<V>> for SymmetricGroup<V>
 where
-    V: Clone + Copy + Hash + Eq + PartialEq + Debug,
+    V: Clone + Copy + Hash + Eq + PartialEq + Debug + Send + Sync,
 {
-    fn get_set(&self) -> HashSet<Cycle<V>> {
-        let mut permutations = HashSet::new();
-        let mut data = self.ground.clone();
-        heap_recursive(&mut data, |permutation| {
-            permutations.insert(self.create_retraversal(permutation));
-        });
-        permutations
+    /// In the order picked with with_enumeration, lexicographic (so the k-th element has rank k) by default.
+    fn elements(&self) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        Box::new(Permutations::new(&self.ground, self.enumeration))
+    }
+
+    fn elements_range(
+        &self,
+        positions: Range<u128>,
+    ) -> Box<dyn Iterator<Item = Cycle<V>> + Send + '_> {
+        Box::new(Permutations::range(
+            &self.ground,
+            self.enumeration,
+            positions,
+        ))
+    }
+
+    fn seekable(&self) -> bool {
+        true
+    }
+
+    /// Every worker lists its own chunks, so the elements never pass through one thread.
+    fn par_elements(&self) -> impl ParallelIterator<Item = Cycle<V>> + '_ {
+        self.chunks(4 * rayon::current_num_threads())
+            .into_par_iter()
+            .flat_map_iter(|chunk| chunk)
     }
 
     ///Note this operation is from right to left (like normal functions)
@@ -73,7 +194,7 @@ where
     }
 
     fn identity(&self) -> Cycle<V> {
-        Cycle::from(vec![vec![]], self.ground.clone())
+        Cycle::identity(&self.ground)
     }
 
     fn inverse(&self, e: Cycle<V>) -> Cycle<V> {
@@ -82,24 +203,16 @@ where
 
     /// The order of a symmetric group means the size.
     /// Computing the size is too hard, so we will just use the following formula instead.
-    fn order(&self) -> i32 {
-        (1..=self.n as i32).product()
+    /// n! only fits in a u128 up to n = 34, past that this panics.
+    fn order(&self) -> u128 {
+        checked_factorial(self.n)
+            .unwrap_or_else(|| panic!("the order of S_{} doesn't fit in a u128", self.n))
     }
 
     /// In Symmetric Groups, we can generate every single n! possible permutations by only combining and adding adjacent transpositions, ie (a_i, a_{i + 1}) for all i.
-    /// We can also perhaps use a different class of generators, but this is fine for now.
+    /// Those are the default, other generating sets can be picked with with_generating_set.
     fn get_generator(&self) -> Vec<Cycle<V>> {
-        self.ground
-            .windows(2)
-            .map(|window| {
-                let prev = window[0];
-                let curr = window[1];
-                let mut map = BiMap::new();
-                map.insert(prev, curr);
-                map.insert(curr, prev);
-                Cycle::new(map, self.ground.clone())
-            })
-            .collect()
+        self.generating_set.generators(&self.ground)
     }
 }
 
@@ -110,48 +223,101 @@ mod tests {
     #[test]
     fn test_symmetric_3() {
         let ground: Vec<i32> = vec![1, 2, 3];
-        let group: SymmetricGroup<i32> = SymmetricGroup::new((&ground).len(), ground.clone());
+        let group: SymmetricGroup<i32> = SymmetricGroup::new(ground.len(), ground.clone());
         let symmetric_set = group.get_set();
 
-        debug_assert_eq!(
-            symmetric_set.contains(&Cycle::from(vec![vec![1, 2, 3]], ground.clone())),
-            true
-        );
-        debug_assert_eq!(
-            symmetric_set.contains(&Cycle::from(vec![vec![1, 2]], ground.clone())),
-            true
-        );
-        debug_assert_eq!(
-            symmetric_set.contains(&Cycle::from(vec![vec![1, 3]], ground.clone())),
-            true
-        );
-        debug_assert_eq!(
-            symmetric_set.contains(&Cycle::from(vec![vec![2, 3]], ground.clone())),
-            true
-        );
-        debug_assert_eq!(
-            symmetric_set.contains(&Cycle::from(vec![vec![3, 2, 1]], ground.clone())),
-            true
-        );
-        debug_assert_eq!(
-            symmetric_set.contains(&Cycle::from(vec![vec![3, 1, 2]], ground.clone())),
-            true
-        );
+        debug_assert!(symmetric_set.contains(&Cycle::from(vec![vec![1, 2, 3]], ground.clone())));
+        debug_assert!(symmetric_set.contains(&Cycle::from(vec![vec![1, 2]], ground.clone())));
+        debug_assert!(symmetric_set.contains(&Cycle::from(vec![vec![1, 3]], ground.clone())));
+        debug_assert!(symmetric_set.contains(&Cycle::from(vec![vec![2, 3]], ground.clone())));
+        debug_assert!(symmetric_set.contains(&Cycle::from(vec![vec![3, 2, 1]], ground.clone())));
+        debug_assert!(symmetric_set.contains(&Cycle::from(vec![vec![3, 1, 2]], ground.clone())));
     }
     #[test]
     fn test_symmetric_5() {
         let ground: Vec<i32> = vec![1, 2, 3, 4, 5];
-        let group: SymmetricGroup<i32> = SymmetricGroup::new((&ground).len(), ground.clone());
+        let group: SymmetricGroup<i32> = SymmetricGroup::new(ground.len(), ground.clone());
         let symmetric_set = group.get_set();
-        debug_assert_eq!(group.order(), symmetric_set.len() as i32);
+        debug_assert_eq!(group.order(), symmetric_set.len() as u128);
         debug_assert_eq!(group.order(), 120);
     }
 
     #[test]
     fn test_symmetric_8() {
         let ground: Vec<i32> = vec![1, 2, 3, 4, 5, 6, 7, 8];
-        let group: SymmetricGroup<i32> = SymmetricGroup::new((&ground).len(), ground.clone());
+        let group: SymmetricGroup<i32> = SymmetricGroup::new(ground.len(), ground.clone());
         let symmetric_set = group.get_set();
         debug_assert_eq!(40320, symmetric_set.len() as i32);
     }
+
+    #[test]
+    fn rank_lexicographic() {
+        let group = crate::group_theory::symmetric::sym(4);
+        let mut all: Vec<Cycle<usize>> = group.get_set().into_iter().collect();
+        all.sort_by_key(|cycle| cycle.images().to_vec());
+        for (k, cycle) in all.iter().enumerate() {
+            debug_assert_eq!(group.rank(cycle), k as u128);
+            debug_assert_eq!(&group.unrank(k as u128), cycle);
+        }
+        debug_assert_eq!(group.unrank(0), group.identity());
+        debug_assert_eq!(group.unrank(23).get_retraversal_str(), "4,3,2,1");
+        debug_assert_eq!(group.try_unrank(23), Some(group.unrank(23)));
+        debug_assert_eq!(group.try_unrank(24), None);
+        debug_assert_eq!(group.ranked(5..8).count(), 3);
+    }
+
+    #[test]
+    fn rank_large() {
+        let group = crate::group_theory::symmetric::sym(30);
+        let k = 123_456_789_012_345_678_901_234_567u128;
+        debug_assert_eq!(group.rank(&group.unrank(k)), k);
+        let cycle = group.unrank(k);
+        debug_assert_eq!(
+            cycle.lehmer_code().iter().sum::<usize>(),
+            cycle.inversions()
+        );
+        debug_assert_eq!(
+            Cycle::from_lehmer_code(&cycle.lehmer_code(), group.ground_set()),
+            Ok(cycle)
+        );
+    }
+
+    #[test]
+    fn gray_code_elements() {
+        use crate::group_theory::enumeration::EnumerationOrder;
+        use rayon::iter::ParallelIterator;
+        use std::collections::HashSet;
+
+        let group = crate::group_theory::symmetric::sym(6).with_enumeration(EnumerationOrder::Heap);
+        let listed: Vec<Cycle<usize>> = group.elements().collect();
+        debug_assert_eq!(listed.len() as u128, group.order());
+        debug_assert_eq!(
+            group.par_elements().collect::<HashSet<_>>(),
+            group.get_set()
+        );
+        let joined: Vec<Cycle<usize>> = group.chunks(5).into_iter().flatten().collect();
+        debug_assert_eq!(joined, listed);
+    }
+
+    #[test]
+    fn lazy_elements() {
+        use rayon::iter::ParallelIterator;
+
+        let group = crate::group_theory::symmetric::sym(5);
+        let ranked: Vec<Cycle<usize>> = group.ranked(0..120).collect();
+        debug_assert_eq!(group.elements().collect::<Vec<_>>(), ranked);
+        debug_assert_eq!(group.par_elements().count(), 120);
+        // 20! overflowed the old i32 order, 34! is the largest that fits
+        let big = crate::group_theory::symmetric::sym(34);
+        debug_assert_eq!(
+            crate::group_theory::symmetric::sym(20).order(),
+            2_432_902_008_176_640_000
+        );
+        debug_assert_eq!(
+            big.order() / 34,
+            crate::group_theory::symmetric::sym(33).order()
+        );
+        debug_assert_eq!(big.elements().take(3).count(), 3);
+        debug_assert_eq!(big.elements_range(1000..1003).count(), 3);
+    }
 }
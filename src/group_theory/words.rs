@@ -0,0 +1,167 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::group_theory::cycle::{Cycle, PermutationError};
+use crate::group_theory::ground::GroundSet;
+
+/// The left descents of w: the letters i where w^-1(i) > w^-1(i + 1), meaning s_i * w has one less inversion.
+/// `position` is w^-1 as an image array.
+fn left_descents(position: &[u32]) -> impl Iterator<Item = usize> + '_ {
+    position
+        .windows(2)
+        .enumerate()
+        .filter(|(_, p)| p[0] > p[1])
+        .map(|(i, _)| i)
+}
+
+/// Words are written over the adjacent transpositions SymmetricGroup::get_generator gives,
+/// so letter i is s_i, the swap of the i-th and (i + 1)-th element of the ground set (counting from 0).
+/// The word [a, b, c] is the product s_a * s_b * s_c, which composes right to left like Mul.
+/// A word is reduced when no shorter word gives the same permutation, and then its length is the number of inversions.
+impl<T> Cycle<T>
+where
+    T: Debug + Clone + Eq + Hash + 'static,
+{
+    /// The permutation s_word[0] * s_word[1] * ... Fails if a letter has no matching generator.
+    pub fn from_word(
+        word: &[usize],
+        ground: impl Into<GroundSet<T>>,
+    ) -> Result<Self, PermutationError> {
+        let ground = ground.into();
+        let mut image: Vec<u32> = (0..ground.len() as u32).collect();
+        // multiplying by s_i on the right swaps positions i and i + 1, so apply the letters from the left end
+        for (position, &value) in word.iter().enumerate() {
+            if value + 1 >= ground.len() {
+                return Err(PermutationError::OutOfRange { position, value });
+            }
+            image.swap(value, value + 1);
+        }
+        Ok(Cycle::from_images(image, ground))
+    }
+
+    /// The letter i if this is the adjacent transposition s_i.
+    pub fn as_adjacent_transposition(&self) -> Option<usize> {
+        let moved: Vec<usize> = (0..self.n())
+            .filter(|&i| self.images()[i] != i as u32)
+            .collect();
+        match moved[..] {
+            [i, j] if j == i + 1 && self.images()[i] == j as u32 => Some(i),
+            _ => None,
+        }
+    }
+
+    /// The lexicographically smallest reduced word, found by always taking the smallest left descent.
+    pub fn reduced_word(&self) -> Vec<usize> {
+        let mut position = self.inverse().images().to_vec();
+        let mut word = Vec::with_capacity(self.inversions());
+        loop {
+            let Some(i) = left_descents(&position).next() else {
+                break;
+            };
+            // s_i * w swaps where the values i and i + 1 are
+            position.swap(i, i + 1);
+            word.push(i);
+        }
+        word
+    }
+
+    /// Every reduced word, in lexicographic order. There can be a lot of them (the reversal in S_n has
+    /// (n choose 2)! / (1^(n-1) 3^(n-2) ... (2n-3)^1) of them), so they are produced lazily.
+    pub fn reduced_words(&self) -> ReducedWords {
+        ReducedWords {
+            stack: vec![(self.inverse().images().to_vec(), Vec::new())],
+        }
+    }
+}
+
+/// Depth first search over left descents, see Cycle::reduced_words.
+pub struct ReducedWords {
+    /// (w^-1 still to be factored, the letters already taken off the left of w)
+    stack: Vec<(Vec<u32>, Vec<usize>)>,
+}
+
+impl Iterator for ReducedWords {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((position, prefix)) = self.stack.pop() {
+            let descents: Vec<usize> = left_descents(&position).collect();
+            if descents.is_empty() {
+                return Some(prefix);
+            }
+            // pushed largest first so the smallest letter is explored first
+            for &i in descents.iter().rev() {
+                let mut next_position = position.clone();
+                next_position.swap(i, i + 1);
+                let mut next_prefix = prefix.clone();
+                next_prefix.push(i);
+                self.stack.push((next_position, next_prefix));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::group_theory::cycle::{Cycle, PermutationError};
+    use crate::group_theory::group::Group;
+    use crate::group_theory::symmetric::sym;
+
+    #[test]
+    fn word_round_trip() {
+        let s_5 = sym(5);
+        for w in s_5.get_set() {
+            let word = w.reduced_word();
+            debug_assert_eq!(word.len(), w.inversions());
+            debug_assert_eq!(Cycle::from_word(&word, s_5.ground_set()), Ok(w));
+        }
+    }
+
+    #[test]
+    fn word_matches_generators() {
+        let s_4 = sym(4);
+        let gens = s_4.get_generator();
+        let word = vec![0, 2, 1, 0];
+        let product = word
+            .iter()
+            .fold(s_4.identity(), |acc, &i| acc * gens[i].clone());
+        debug_assert_eq!(Cycle::from_word(&word, s_4.ground_set()), Ok(product));
+        for (i, gen) in gens.iter().enumerate() {
+            debug_assert_eq!(gen.as_adjacent_transposition(), Some(i));
+        }
+        debug_assert_eq!(s_4.identity().as_adjacent_transposition(), None);
+        debug_assert_eq!(
+            s_4.create_vec(vec![vec![1, 3]]).as_adjacent_transposition(),
+            None
+        );
+        debug_assert_eq!(
+            Cycle::from_word(&[0, 3], s_4.ground_set()),
+            Err(PermutationError::OutOfRange {
+                position: 1,
+                value: 3
+            })
+        );
+    }
+
+    #[test]
+    fn all_reduced_words() {
+        let s_4 = sym(4);
+        let w0 = s_4.create_retraversal(&[4, 3, 2, 1]);
+        let words: Vec<Vec<usize>> = w0.reduced_words().collect();
+        // the longest element of S_4 has 16 reduced words
+        debug_assert_eq!(words.len(), 16);
+        debug_assert_eq!(words[0], w0.reduced_word());
+        debug_assert!(words.windows(2).all(|w| w[0] < w[1]));
+        debug_assert_eq!(words.iter().collect::<HashSet<_>>().len(), 16);
+        for word in words {
+            debug_assert_eq!(Cycle::from_word(&word, s_4.ground_set()), Ok(w0.clone()));
+        }
+        debug_assert_eq!(
+            s_4.identity().reduced_words().collect::<Vec<_>>(),
+            vec![Vec::<usize>::new()]
+        );
+    }
+}
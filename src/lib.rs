@@ -1,13 +1,32 @@
 pub mod group_theory {
+    pub mod action;
+    pub mod alternating;
+    pub mod conjugacy;
     pub mod cycle;
+    pub mod cyclic;
+    pub mod dihedral;
+    pub mod enumeration;
+    pub mod generating;
+    pub mod ground;
     pub mod group;
+    pub mod notation;
+    pub mod orbit;
+    pub mod permutation_group;
+    pub mod poset;
+    pub mod presentation;
+    pub mod product;
+    pub mod statistics;
     pub mod symmetric;
+    pub mod words;
 }
 
 pub mod generator {
+    pub mod expression;
     pub mod gen;
     pub mod iterative;
+    pub mod markov;
     pub mod periodic;
+    pub mod random;
 }
 
 pub mod graph {
@@ -1,15 +1,19 @@
 use std::cmp::min;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::str::FromStr;
 
-use serde::Serialize;
+use indexmap::IndexSet;
+use serde::{Deserialize, Serialize};
 
 use crate::group_theory::cycle::Cycle;
 use crate::group_theory::group::Group;
+use crate::group_theory::poset::PermutationOrder;
 use crate::group_theory::symmetric::SymmetricGroup;
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(bound(deserialize = "V: FromStr + TryFrom<usize>"))]
 pub struct ChainFindResult<V>
 where
     V: Clone + Copy + Hash + Eq + PartialEq + Debug + PartialOrd + ToString + 'static,
@@ -18,8 +22,37 @@ where
     pub length_chain: usize,
     pub chain: Vec<Cycle<V>>,
     pub non_unique_choices: HashMap<String, Vec<Cycle<V>>>,
+    /// The chain written as a reduced word of its last element, see chain_word.
+    #[serde(default)]
+    pub word: Vec<usize>,
 }
 
+/// Writes a chain as a word over the adjacent transpositions (see Cycle::reduced_word).
+/// Chains found with other generating sets usually have no such word.
+/// Every step of the chain has to multiply by one generator, on either side. Steps on the left go to the front
+/// of the word and steps on the right go to the back, with a reduced word of the first element in between,
+/// so the word is a word of the last element. When every step adds an inversion, like in chain_find, it is reduced.
+/// Returns None if some step is not a single generator.
+pub fn chain_word<V>(chain: &[Cycle<V>]) -> Option<Vec<usize>>
+where
+    V: Clone + Hash + Eq + Debug,
+{
+    let mut word = VecDeque::from(chain.first()?.reduced_word());
+    for step in chain.windows(2) {
+        let (prev, next) = (&step[0], &step[1]);
+        if let Some(i) = (&prev.inverse() * next).as_adjacent_transposition() {
+            word.push_back(i);
+        } else {
+            word.push_front((next * &prev.inverse()).as_adjacent_transposition()?);
+        }
+    }
+    Some(word.into())
+}
+
+/// Greedily climbs from start, one generator of the group at a time (on either side), always going up by one in the
+/// length of the group's generating set and taking the first such step, in generator order.
+/// Generating sets without a length formula get their lengths from SymmetricGroup::distances, which lists all of S_n.
+/// If those generators don't reach start, the chain is just start.
 #[allow(unused)]
 pub fn chain_find<V, F, O>(
     group: &SymmetricGroup<V>,
@@ -28,36 +61,81 @@ pub fn chain_find<V, F, O>(
     maxlen: usize,
 ) -> ChainFindResult<V>
 where
-    V: Clone + Copy + Hash + Eq + PartialEq + Debug + PartialOrd + ToString,
+    V: Clone + Copy + Hash + Eq + PartialEq + Debug + PartialOrd + ToString + Send + Sync,
+    F: Fn(&Cycle<V>) -> O,
+    O: PartialOrd + PartialEq,
+{
+    search(group, start, None, locality_calc, maxlen)
+}
+
+/// Same as chain_find, but every element of the chain stays inside the interval [start, end] of the order.
+/// The chain stops early if it reaches end, or if no generator step stays inside the interval.
+pub fn chain_find_in<V, F, O>(
+    group: &SymmetricGroup<V>,
+    start: Cycle<V>,
+    end: &Cycle<V>,
+    order: PermutationOrder,
+    locality_calc: F,
+    maxlen: usize,
+) -> ChainFindResult<V>
+where
+    V: Clone + Copy + Hash + Eq + PartialEq + Debug + PartialOrd + ToString + Send + Sync,
+    F: Fn(&Cycle<V>) -> O,
+    O: PartialOrd + PartialEq,
+{
+    search(group, start, Some((end, order)), locality_calc, maxlen)
+}
+
+fn search<V, F, O>(
+    group: &SymmetricGroup<V>,
+    start: Cycle<V>,
+    bound: Option<(&Cycle<V>, PermutationOrder)>,
+    locality_calc: F,
+    maxlen: usize,
+) -> ChainFindResult<V>
+where
+    V: Clone + Copy + Hash + Eq + PartialEq + Debug + PartialOrd + ToString + Send + Sync,
     F: Fn(&Cycle<V>) -> O,
     O: PartialOrd + PartialEq,
 {
     let generators = group.get_generator();
+    let distances = group.length(&start).is_none().then(|| group.distances());
+    let length = |w: &Cycle<V>| {
+        group
+            .length(w)
+            .or_else(|| distances.as_ref()?.get(w).copied())
+    };
     let mut res = VecDeque::new();
     res.push_back(start.clone());
-    let mut curr_length: usize = start.inversions();
-    let max_length = min(
-        (group.ground_size() * (group.ground_size() + 1)) / 2,
-        maxlen,
-    );
+    let diameter = group.diameter().unwrap_or_else(|| {
+        distances
+            .as_ref()
+            .and_then(|d| d.values().copied().max())
+            .unwrap_or(0)
+    });
+    // a start the generators can't reach has no length, so the chain can't climb from it
+    let mut curr_length = length(&start).unwrap_or(0);
+    let mut max_length = match length(&start) {
+        Some(_) => min(diameter, maxlen),
+        None => 0,
+    };
+    if let Some((end, _)) = bound {
+        max_length = min(max_length, length(end).unwrap_or(0));
+    }
     let mut non_unique_map = HashMap::with_capacity(20);
     let mut non_unique = 0;
     while curr_length < max_length {
         let node = res.back().unwrap();
-        let left_map: Vec<Cycle<V>> = generators
-            .iter()
-            .map(|gen| node.clone() * gen.clone())
-            .collect();
-        let right_map: Vec<Cycle<V>> = generators
-            .iter()
-            .map(|gen| gen.clone() * node.clone())
-            .collect();
+        let left_map: Vec<Cycle<V>> = generators.iter().map(|gen| node * gen).collect();
+        let right_map: Vec<Cycle<V>> = generators.iter().map(|gen| gen * node).collect();
         let total = [&left_map[..], &right_map[..]].concat();
-        let mut max_locality = total
+        // IndexSet keeps the first-seen order, so the chain picked is the same on every run.
+        let max_locality = total
             .iter()
-            .filter(|x| x.inversions() == node.inversions() + 1)
-            .collect::<HashSet<_>>();
-        let max_locality: Vec<_> = max_locality.iter().cloned().collect();
+            .filter(|x| length(x) == length(node).map(|l| l + 1))
+            .filter(|x| bound.is_none_or(|(end, order)| x.le(end, order)))
+            .collect::<IndexSet<_>>();
+        let max_locality: Vec<_> = max_locality.into_iter().collect();
         if let Some(&first) = max_locality.first() {
             if max_locality.len() > 1
                 && locality_calc(first) == locality_calc(max_locality.get(1).unwrap())
@@ -80,24 +158,26 @@ where
         curr_length += 1;
     }
 
+    let chain: Vec<Cycle<V>> = res.into();
     ChainFindResult {
         length_non_unique: non_unique,
         length_chain: curr_length,
-        chain: res.into(),
+        word: chain_word(&chain).unwrap_or_default(),
+        chain,
         non_unique_choices: non_unique_map,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::usize;
-
     use crate::generator::gen::Generator;
     use crate::generator::periodic::PeriodicGen;
     use crate::group_theory::cycle::Cycle;
+    use crate::group_theory::generating::GeneratingSet;
     use crate::group_theory::group::Group;
-    use crate::group_theory::symmetric::SymmetricGroup;
-    use crate::locality::chainfind::{chain_find, ChainFindResult};
+    use crate::group_theory::poset::PermutationOrder;
+    use crate::group_theory::symmetric::{sym, SymmetricGroup};
+    use crate::locality::chainfind::{chain_find, chain_find_in, chain_word, ChainFindResult};
     use crate::locality::reuse::calculate_lru_hits;
 
     #[test]
@@ -122,13 +202,109 @@ mod tests {
             |retraversal| hits_ranking(retraversal),
             usize::MAX,
         );
-        println!("{:?}", chain);
-        debug_assert!(&chain.contains(&s_m.create_vec(vec![vec![]])));
-        //debug_assert!(&chain.contains(&s_m.create_vec(vec![vec![3, 4]])));
-        debug_assert!(&chain.contains(&s_m.create_vec(vec![vec![1, 3, 2]])));
-        debug_assert!(&chain.contains(&s_m.create_vec(vec![vec![1, 4, 3, 2]])));
-        //debug_assert!(&chain.contains(&s_m.create_vec(vec![vec![1, 4, 3]])));
-        debug_assert!(&chain.contains(&s_m.create_vec(vec![vec![1, 4, 2, 3]])));
-        debug_assert!(&chain.contains(&s_m.create_vec(vec![vec![1, 4], vec![2, 3]])));
+        // every step takes the first candidate in generator order, whatever the locality
+        let expected = vec![
+            s_m.create_vec(vec![vec![]]),
+            s_m.create_vec(vec![vec![1, 2]]),
+            s_m.create_vec(vec![vec![1, 2, 3]]),
+            s_m.create_vec(vec![vec![1, 3]]),
+            s_m.create_vec(vec![vec![1, 3, 4]]),
+            s_m.create_vec(vec![vec![1, 3, 2, 4]]),
+            s_m.create_vec(vec![vec![1, 4], vec![2, 3]]),
+        ];
+        debug_assert_eq!(chain, expected);
+    }
+
+    #[test]
+    fn chain_round_trip() {
+        let s_m = sym(4);
+        let result = chain_find(&s_m, s_m.identity(), |cycle| cycle.inversions(), usize::MAX);
+        let serialized = serde_json::to_string(&result).unwrap();
+        let loaded: ChainFindResult<usize> = serde_json::from_str(&serialized).unwrap();
+        debug_assert_eq!(loaded.chain, result.chain);
+        debug_assert_eq!(loaded.length_chain, result.length_chain);
+        debug_assert_eq!(loaded.non_unique_choices, result.non_unique_choices);
+    }
+
+    #[test]
+    fn chain_is_reduced_word() {
+        let s_m = sym(5);
+        let start = s_m.create_vec(vec![vec![2, 3]]);
+        let result = chain_find(&s_m, start, |cycle| cycle.major_index(), usize::MAX);
+        let last = result.chain.last().unwrap();
+        debug_assert_eq!(result.word.len(), last.inversions());
+        debug_assert_eq!(
+            Cycle::from_word(&result.word, s_m.ground_set()).as_ref(),
+            Ok(last)
+        );
+        debug_assert_eq!(chain_word(&result.chain), Some(result.word));
+        debug_assert_eq!(
+            chain_word(&[s_m.identity(), s_m.create_vec(vec![vec![1, 3]])]),
+            None
+        );
+    }
+
+    #[test]
+    fn chain_in_interval() {
+        let s_m = sym(5);
+        let end = s_m.create_retraversal(&[3, 1, 2, 5, 4]);
+        for order in [
+            PermutationOrder::LeftWeak,
+            PermutationOrder::RightWeak,
+            PermutationOrder::Bruhat,
+        ] {
+            let result = chain_find_in(
+                &s_m,
+                s_m.identity(),
+                &end,
+                order,
+                |cycle| cycle.inversions(),
+                usize::MAX,
+            );
+            debug_assert!(result.chain.iter().all(|w| w.le(&end, order)));
+            debug_assert_eq!(result.chain.last(), Some(&end));
+        }
+    }
+
+    #[test]
+    fn chains_in_other_generating_sets() {
+        for set in [
+            GeneratingSet::Star,
+            GeneratingSet::Transpositions,
+            GeneratingSet::Coxeter,
+            GeneratingSet::PrefixReversals,
+        ] {
+            let s_m = sym(5).with_generating_set(set);
+            let distances = s_m.distances();
+            let result = chain_find(
+                &s_m,
+                s_m.identity(),
+                |cycle| cycle.major_index(),
+                usize::MAX,
+            );
+            let generators = s_m.get_generator();
+            for (i, step) in result.chain.windows(2).enumerate() {
+                debug_assert_eq!(distances[&step[1]], i + 1);
+                debug_assert!(generators
+                    .iter()
+                    .any(|g| &step[0] * g == step[1] || g * &step[0] == step[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn start_out_of_reach() {
+        // (1,2) and (3,4) only reach 4 elements of S_4
+        let s_m = sym(4);
+        let generators = vec![
+            s_m.create_vec(vec![vec![1, 2]]),
+            s_m.create_vec(vec![vec![3, 4]]),
+        ];
+        let s_m = s_m.with_generating_set(GeneratingSet::Custom(generators));
+        let start = s_m.create_vec(vec![vec![1, 3]]);
+        let result = chain_find(&s_m, start.clone(), |cycle| cycle.inversions(), usize::MAX);
+        debug_assert_eq!(result.chain, vec![start]);
+        let result = chain_find(&s_m, s_m.identity(), |cycle| cycle.inversions(), usize::MAX);
+        debug_assert_eq!(result.chain.len(), 3);
     }
 }
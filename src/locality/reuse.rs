@@ -1,5 +1,7 @@
+use crate::generator::gen::Sink;
 use crate::math::combinations::{combinations, factorial};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
 use std::fmt::Debug;
 use std::hash::Hash;
 
@@ -48,6 +50,58 @@ where
         .count()
 }
 
+/// An LRU cache counting its hits as the accesses stream in, the same count as calculate_lru_hits gives for the
+/// whole trace. Only the cached elements are kept, so a trace of any length can be fed to it with simulate_into.
+#[derive(Debug, Clone)]
+pub struct LruHits<T> {
+    cache_size: usize,
+    /// Most recently used first.
+    stack: VecDeque<T>,
+    hits: usize,
+    accesses: usize,
+}
+
+impl<T: PartialEq> LruHits<T> {
+    pub fn new(cache_size: usize) -> Self {
+        LruHits {
+            cache_size,
+            stack: VecDeque::with_capacity(cache_size + 1),
+            hits: 0,
+            accesses: 0,
+        }
+    }
+
+    pub fn access(&mut self, access: T) {
+        self.accesses += 1;
+        match self.stack.iter().position(|x| x == &access) {
+            Some(i) => {
+                self.hits += 1;
+                self.stack.remove(i);
+            }
+            None if self.cache_size == 0 => return,
+            None => self.stack.truncate(self.cache_size - 1),
+        }
+        self.stack.push_front(access);
+    }
+
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    pub fn accesses(&self) -> usize {
+        self.accesses
+    }
+}
+
+impl<T: PartialEq> Sink<T> for LruHits<T> {
+    type Error = Infallible;
+
+    fn push(&mut self, access: T) -> Result<(), Infallible> {
+        self.access(access);
+        Ok(())
+    }
+}
+
 #[allow(unused)]
 fn calculate_lru_hits_formula(data_items: i128, cache_size: i128, hits: i128) -> i128 {
     if 2 * cache_size <= data_items + hits {
@@ -79,6 +133,7 @@ mod tests {
     use crate::locality::reuse::calculate_lru_hits;
     use crate::locality::reuse::calculate_reuse_distance;
     use crate::locality::reuse::factorial;
+    use crate::locality::reuse::LruHits;
 
     use super::calculate_lru_hits_formula;
 
@@ -97,25 +152,24 @@ mod tests {
         debug_assert_eq!(num_hits, 2);
     }
 
+    #[test]
+    fn streaming_hits() {
+        let trace = vec![1, 2, 3, 2, 4, 3, 1, 1, 5, 2, 4, 4, 3, 1];
+        for cache_size in 0..=6 {
+            let mut lru = LruHits::new(cache_size);
+            trace.iter().for_each(|x| lru.access(*x));
+            debug_assert_eq!(lru.hits(), calculate_lru_hits(&trace, cache_size));
+            debug_assert_eq!(lru.accesses(), trace.len());
+        }
+    }
+
     #[test]
     fn simple_trace_hits_1() {
         let cache_size = 2;
-        debug_assert_eq!(
-            calculate_lru_hits(&vec![1, 2, 3, 4, 1, 2, 3, 4], cache_size),
-            0
-        );
-        debug_assert_eq!(
-            calculate_lru_hits(&vec![1, 2, 3, 4, 2, 1, 3, 4], cache_size),
-            0
-        );
-        debug_assert_eq!(
-            calculate_lru_hits(&vec![1, 2, 3, 4, 1, 3, 2, 4], cache_size),
-            0
-        );
-        debug_assert_eq!(
-            calculate_lru_hits(&vec![1, 2, 3, 4, 1, 2, 4, 3], cache_size),
-            0
-        );
+        debug_assert_eq!(calculate_lru_hits(&[1, 2, 3, 4, 1, 2, 3, 4], cache_size), 0);
+        debug_assert_eq!(calculate_lru_hits(&[1, 2, 3, 4, 2, 1, 3, 4], cache_size), 0);
+        debug_assert_eq!(calculate_lru_hits(&[1, 2, 3, 4, 1, 3, 2, 4], cache_size), 0);
+        debug_assert_eq!(calculate_lru_hits(&[1, 2, 3, 4, 1, 2, 4, 3], cache_size), 0);
     }
 
     #[test]
@@ -124,7 +178,6 @@ mod tests {
         let cache_size = 5;
 
         let a: i128 = (0..=cache_size)
-            .into_iter()
             .map(|h| calculate_lru_hits_formula(data_items, cache_size, h))
             .sum();
 
@@ -137,7 +190,6 @@ mod tests {
         let cache_size = 2;
 
         let a: i128 = (0..=cache_size)
-            .into_iter()
             .map(|h| calculate_lru_hits_formula(data_items, cache_size, h))
             .sum();
 
@@ -150,7 +202,6 @@ mod tests {
         let cache_size = 4;
 
         let a: i128 = (0..=cache_size)
-            .into_iter()
             .map(|h| calculate_lru_hits_formula(data_items, cache_size, h))
             .sum();
 
@@ -1,18 +1,29 @@
 use abstract_cache::{CacheSim, ObjIdTraits};
-use clap::{Parser, Subcommand, ValueEnum, ValueHint};
-use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum, ValueHint};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use reperm_gen::chain_find;
-use reperm_gen::generator::gen::Generator;
+use reperm_gen::generator::expression::TraceExpression;
+use reperm_gen::generator::gen::{Generator, Lines, Sink};
 use reperm_gen::generator::periodic::PeriodicGen;
+use reperm_gen::group_theory::alternating::alt;
 use reperm_gen::group_theory::cycle::Cycle;
+use reperm_gen::group_theory::cyclic::cyclic;
+use reperm_gen::group_theory::dihedral::dihedral;
+use reperm_gen::group_theory::enumeration::EnumerationOrder;
+use reperm_gen::group_theory::generating::GeneratingSet;
 use reperm_gen::group_theory::group::Group;
+use reperm_gen::group_theory::permutation_group::PermutationGroup;
+use reperm_gen::group_theory::product::{hyperoctahedral, Products};
 use reperm_gen::group_theory::symmetric::sym;
-use reperm_gen::locality::reuse::calculate_lru_hits;
+use reperm_gen::locality::reuse::{calculate_lru_hits, LruHits};
+use reperm_gen::math::combinations::partitions;
 use serde_json::json;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fs::File;
 use std::hash::Hash;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::sync::Arc;
 use tracing::{event, Level};
 
@@ -35,6 +46,268 @@ enum LocalityCalculator {
     LRU,
 }
 
+/// How many elements Plot holds in memory at once.
+const PLOT_CHUNK_SIZE: usize = 1 << 14;
+
+/// The groups Plot can list, all acting on 1..=n.
+#[derive(Debug, Clone, ValueEnum)]
+enum GroupKind {
+    Symmetric,
+    Cyclic,
+    Dihedral,
+    Alternating,
+    /// S_b wr S_(n/b): shuffles within blocks of --block-size and shuffles the blocks.
+    Wreath,
+    /// Signed permutations of n/2 pairs, ie S_2 wr S_(n/2).
+    Hyperoctahedral,
+}
+
+/// The generating sets FindChain can climb with.
+#[derive(Debug, Clone, ValueEnum)]
+enum Generators {
+    Adjacent,
+    Star,
+    Transpositions,
+    Coxeter,
+    Pancake,
+}
+
+impl From<&Generators> for GeneratingSet<usize> {
+    fn from(generators: &Generators) -> Self {
+        match generators {
+            Generators::Adjacent => GeneratingSet::Adjacent,
+            Generators::Star => GeneratingSet::Star,
+            Generators::Transpositions => GeneratingSet::Transpositions,
+            Generators::Coxeter => GeneratingSet::Coxeter,
+            Generators::Pancake => GeneratingSet::PrefixReversals,
+        }
+    }
+}
+
+/// The orders the symmetric group can be listed in.
+#[derive(Debug, Clone, ValueEnum)]
+enum Enumeration {
+    Lexicographic,
+    Heap,
+    Sjt,
+}
+
+impl From<&Enumeration> for EnumerationOrder {
+    fn from(enumeration: &Enumeration) -> Self {
+        match enumeration {
+            Enumeration::Lexicographic => EnumerationOrder::Lexicographic,
+            Enumeration::Heap => EnumerationOrder::Heap,
+            Enumeration::Sjt => EnumerationOrder::SteinhausJohnsonTrotter,
+        }
+    }
+}
+
+fn get_group(
+    kind: &GroupKind,
+    n: usize,
+    enumeration: &Enumeration,
+    block_size: usize,
+) -> Box<dyn Group<Cycle<usize>>> {
+    let block_size = match kind {
+        GroupKind::Hyperoctahedral => 2,
+        _ => block_size,
+    };
+    if matches!(kind, GroupKind::Wreath | GroupKind::Hyperoctahedral)
+        && (block_size == 0 || !n.is_multiple_of(block_size))
+    {
+        Cli::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!("{} can't be cut into blocks of {}", n, block_size),
+            )
+            .exit()
+    }
+    match kind {
+        GroupKind::Symmetric => Box::new(sym(n).with_enumeration(enumeration.into())),
+        GroupKind::Cyclic => Box::new(cyclic(n)),
+        GroupKind::Dihedral => Box::new(dihedral(n)),
+        GroupKind::Alternating => Box::new(alt(n)),
+        GroupKind::Wreath => Box::new(sym(block_size).wreath_product(&sym(n / block_size))),
+        GroupKind::Hyperoctahedral => Box::new(hyperoctahedral(n / 2)),
+    }
+}
+
+/// Permutation statistics that Plot can write next to the locality of each permutation.
+#[derive(Debug, Clone, ValueEnum)]
+enum Statistic {
+    Sign,
+    Order,
+    CycleType,
+    Cycles,
+    FixedPoints,
+    Descents,
+    DescentSet,
+    MajorIndex,
+    Excedances,
+    Lis,
+    Lds,
+    Peaks,
+}
+
+impl Statistic {
+    fn header(&self) -> String {
+        format!("\"{}\"", self.to_possible_value().unwrap().get_name())
+    }
+
+    /// The csv cell for this statistic; set valued statistics are quoted.
+    fn evaluate<V>(&self, cycle: &Cycle<V>) -> String
+    where
+        V: Clone + Hash + Eq + Debug,
+    {
+        let join = |v: Vec<usize>| {
+            format!(
+                "\"{}\"",
+                v.iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            )
+        };
+        match self {
+            Statistic::Sign => cycle.sign().to_string(),
+            Statistic::Order => cycle.order().to_string(),
+            Statistic::CycleType => join(cycle.cycle_type()),
+            Statistic::Cycles => cycle.num_cycles().to_string(),
+            Statistic::FixedPoints => cycle.fixed_points().to_string(),
+            Statistic::Descents => cycle.descents().to_string(),
+            Statistic::DescentSet => join(cycle.descent_set()),
+            Statistic::MajorIndex => cycle.major_index().to_string(),
+            Statistic::Excedances => cycle.excedances().to_string(),
+            Statistic::Lis => cycle.longest_increasing_subsequence().to_string(),
+            Statistic::Lds => cycle.longest_decreasing_subsequence().to_string(),
+            Statistic::Peaks => cycle.peaks().to_string(),
+        }
+    }
+}
+
+/// The spread of the locality over the elements of one cycle type, for every cache size.
+#[derive(Debug, Clone, Default)]
+struct ClassSummary {
+    count: u64,
+    sum: Vec<f64>,
+    sum_squares: Vec<f64>,
+    min: Vec<f32>,
+    max: Vec<f32>,
+}
+
+impl ClassSummary {
+    fn add(&mut self, locality: &[f32]) {
+        if self.count == 0 {
+            self.sum = vec![0.0; locality.len()];
+            self.sum_squares = vec![0.0; locality.len()];
+            self.min = locality.to_vec();
+            self.max = locality.to_vec();
+        }
+        self.count += 1;
+        for (i, &x) in locality.iter().enumerate() {
+            self.sum[i] += x as f64;
+            self.sum_squares[i] += x as f64 * x as f64;
+            self.min[i] = self.min[i].min(x);
+            self.max[i] = self.max[i].max(x);
+        }
+    }
+
+    fn merge(mut self, other: ClassSummary) -> ClassSummary {
+        if self.count == 0 {
+            return other;
+        }
+        if other.count > 0 {
+            self.count += other.count;
+            for i in 0..self.sum.len() {
+                self.sum[i] += other.sum[i];
+                self.sum_squares[i] += other.sum_squares[i];
+                self.min[i] = self.min[i].min(other.min[i]);
+                self.max[i] = self.max[i].max(other.max[i]);
+            }
+        }
+        self
+    }
+
+    /// The mean, min, max and (population) standard deviation for each cache size.
+    fn cells(&self) -> String {
+        let count = self.count as f64;
+        (0..self.sum.len())
+            .map(|i| {
+                let mean = self.sum[i] / count;
+                let variance = (self.sum_squares[i] / count - mean * mean).max(0.0);
+                format!(
+                    "{},{},{},{}",
+                    mean,
+                    self.min[i],
+                    self.max[i],
+                    variance.sqrt()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+type ClassSummaries = HashMap<Vec<usize>, ClassSummary>;
+
+fn merge_summaries(mut a: ClassSummaries, b: ClassSummaries) -> ClassSummaries {
+    for (cycle_type, summary) in b {
+        let merged = a.remove(&cycle_type).unwrap_or_default().merge(summary);
+        a.insert(cycle_type, merged);
+    }
+    a
+}
+
+/// Goes through the group PLOT_CHUNK_SIZE elements at a time, spreading each batch over the rayon threads. Every
+/// element is folded into a piece with `visit`, the pieces of a batch are merged in order and the batch is handed to
+/// `done` before the next one is listed. Groups that can seek are cut into pieces each worker lists itself, the others
+/// are walked once and shared out.
+fn in_batches<R>(
+    group: &dyn Group<Cycle<usize>>,
+    visit: impl Fn(R, Cycle<usize>) -> R + Sync,
+    merge: impl Fn(R, R) -> R + Sync,
+    mut done: impl FnMut(R) -> std::io::Result<()>,
+) -> std::io::Result<()>
+where
+    R: Default + Send,
+{
+    let pieces = rayon::current_num_threads();
+    if !group.seekable() {
+        let mut elements = group.elements();
+        loop {
+            let batch: Vec<Cycle<usize>> = elements.by_ref().take(PLOT_CHUNK_SIZE).collect();
+            if batch.is_empty() {
+                return Ok(());
+            }
+            done(
+                batch
+                    .into_par_iter()
+                    .fold(R::default, &visit)
+                    .reduce(R::default, &merge),
+            )?;
+        }
+    }
+    let order = group.order();
+    let mut start = 0;
+    while start < order {
+        let end = order.min(start + PLOT_CHUNK_SIZE as u128);
+        let size = (end - start).div_ceil(pieces as u128);
+        done(
+            (0..pieces)
+                .into_par_iter()
+                .map(|i| {
+                    let from = end.min(start + i as u128 * size);
+                    group
+                        .elements_range(from..end.min(from + size))
+                        .fold(R::default(), &visit)
+                })
+                .reduce(R::default, &merge),
+        )?;
+        start = end;
+    }
+    Ok(())
+}
+
 #[derive(Parser)]
 #[command(
     name = "symmmetric locality",
@@ -62,6 +335,27 @@ enum Commands {
         #[arg(short = 'z', long, action = clap::ArgAction::SetTrue, default_value_t = false)]
         sorted: bool,
 
+        /// Which group on 1..=n to plot the elements of.
+        #[arg(short, long, value_enum, default_value_t = GroupKind::Symmetric)]
+        group: GroupKind,
+
+        /// The size of the blocks the wreath group shuffles.
+        #[arg(short, long, default_value_t = 2)]
+        block_size: usize,
+
+        /// The order the symmetric group is listed in, when it isn't sorted.
+        #[arg(short, long, value_enum, default_value_t = Enumeration::Lexicographic)]
+        enumeration: Enumeration,
+
+        /// Extra permutation statistics to write as columns, ie --statistics sign,major-index
+        #[arg(short = 't', long, value_delimiter = ',')]
+        statistics: Vec<Statistic>,
+
+        /// Writes one row per cycle type (conjugacy class of S_n) instead of one per element, with the mean, min, max
+        /// and standard deviation of the locality over the elements of that type.
+        #[arg(short = 'y', long, action = clap::ArgAction::SetTrue, default_value_t = false, conflicts_with_all = ["sorted", "statistics"])]
+        by_cycle_type: bool,
+
         #[arg(short = 'o', long = "output", value_hint = ValueHint::FilePath)]
         output_file: Option<String>,
     },
@@ -75,12 +369,40 @@ enum Commands {
         #[arg(short, long, value_delimiter = ',')]
         cache_capacity_rankings: Vec<usize>,
 
-        #[arg(short = 'x', long, value_delimiter = ',')]
-        start: Option<Vec<usize>>,
+        /// The permutation to start from, in one-line ("3,1,2"), cycle ("(1,3,2)") or two-line ("1 2 3 / 3 1 2") notation.
+        #[arg(short = 'x', long)]
+        start: Option<String>,
+
+        /// The permutation to start from, given by its rank in the lexicographic order of S_n.
+        #[arg(short = 'r', long, conflicts_with = "start")]
+        start_rank: Option<u128>,
 
         #[arg(short, long, default_value_t = usize::MAX)]
         max_length: usize,
 
+        /// The generators each step of the chain multiplies by.
+        #[arg(short, long, value_enum, default_value_t = Generators::Adjacent)]
+        generators: Generators,
+
+        /// Custom generators instead, in any notation --start takes. Can be given more than once.
+        #[arg(long, conflicts_with = "generators")]
+        generator: Vec<String>,
+
+        #[arg(short = 'o', long = "output", value_hint = ValueHint::FilePath)]
+        output_file: Option<String>,
+    },
+    /// Writes the trace of an expression over named permutations of 1..=n, one access per line.
+    Trace {
+        #[arg(short, long, value_parser)]
+        symmetric_n: usize,
+
+        /// Definitions "name = permutation" separated by ';', then the expression, ie "a = (1,2,3); b = 3,2,1; (a b)^3 b a^-1".
+        expression: String,
+
+        /// Writes the LRU hits of the trace for each of these cache sizes instead of the trace.
+        #[arg(short, long, value_delimiter = ',')]
+        cache_capacity_rankings: Vec<usize>,
+
         #[arg(short = 'o', long = "output", value_hint = ValueHint::FilePath)]
         output_file: Option<String>,
     },
@@ -101,9 +423,10 @@ where
 {
     let func = match calc_enum {
         LocalityCalculator::LRU => move |cycle: &Cycle<V>| {
+            // LRU hits don't depend on the labels, so simulate over ground set indices and skip hashing.
             let mut generator = PeriodicGen::new();
-            generator.set_start(&cycle.get_ground());
-            generator.add(cycle.get_function());
+            generator.set_start(&(0..cycle.n() as u32).collect::<Vec<u32>>());
+            generator.add(cycle.get_index_function());
             let simulated = generator.simulate(1);
 
             //let mut v: Vec<f32> =
@@ -135,6 +458,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             locality_calculator,
             cache_capacity_rankings,
             sorted,
+            group,
+            block_size,
+            enumeration,
+            statistics,
+            by_cycle_type,
             output_file,
         } => {
             assert_ne!(
@@ -145,7 +473,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             assert!(cache_capacity_rankings.iter().max().unwrap() <= &symmetric_n);
             event!(
                 Level::INFO,
-                "Started trying to plot elements of the symmetric group"
+                "Started trying to plot elements of the {:?} group",
+                group
             );
             let mut file = if let Some(o) = output_file {
                 File::create(o)?
@@ -155,52 +484,110 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             let cache_capacity_rankings = Arc::new(cache_capacity_rankings);
             let clone = Arc::clone(&cache_capacity_rankings);
-            let group = sym(symmetric_n);
+            let group = get_group(&group, symmetric_n, &enumeration, block_size);
             let locality_calc: Box<LocalityRanker<usize, Vec<f32>>> =
                 get_calc(&locality_calculator, clone);
+            if by_cycle_type {
+                let mut summaries = ClassSummaries::new();
+                in_batches(
+                    &*group,
+                    |mut piece: ClassSummaries, cycle| {
+                        piece
+                            .entry(cycle.cycle_type())
+                            .or_default()
+                            .add(&locality_calc(&cycle));
+                        piece
+                    },
+                    merge_summaries,
+                    |batch| {
+                        summaries = merge_summaries(std::mem::take(&mut summaries), batch);
+                        Ok(())
+                    },
+                )?;
+                let ranking_header = cache_capacity_rankings
+                    .iter()
+                    .map(|c| format!("\"mean_{c}\",\"min_{c}\",\"max_{c}\",\"stddev_{c}\""))
+                    .collect::<Vec<String>>()
+                    .join(",");
+                file.write_all(
+                    format!("\"cycle_type\",\"elements\",{}\n", ranking_header).as_bytes(),
+                )?;
+                // from the n-cycles down to the identity, skipping types the group doesn't have
+                for cycle_type in partitions(symmetric_n) {
+                    if let Some(summary) = summaries.get(&cycle_type) {
+                        let type_str = cycle_type
+                            .iter()
+                            .map(|x| x.to_string())
+                            .collect::<Vec<String>>()
+                            .join(",");
+                        file.write_all(
+                            format!("\"{}\",{},{}\n", type_str, summary.count, summary.cells())
+                                .as_bytes(),
+                        )?;
+                    }
+                }
+                return Ok(());
+            }
             let retraversal_header = String::from("\"inversions\",\"retraversal\",");
+            let statistics_header: String =
+                statistics.iter().map(|stat| stat.header() + ",").collect();
             let ranking_header = cache_capacity_rankings
                 .iter()
                 .map(|x| x.to_string())
                 .collect::<Vec<String>>()
                 .join(",");
-            let header = retraversal_header + &ranking_header;
-            let set = if sorted {
-                let mut s = group.get_set().into_iter().collect::<Vec<_>>();
-                s.sort_unstable_by_key(|cycle| cycle.inversions());
-                s
-            } else {
-                group.get_set().into_iter().collect::<Vec<_>>()
-            };
-            //let set = group.get_set();
-            let text: String = set
-                .par_iter()
-                .map(|retraversal| (retraversal, locality_calc(retraversal)))
-                .map(|(retraversal, locality)| {
-                    let locality_str = locality
-                        .iter()
-                        .map(|x| x.to_string())
-                        .collect::<Vec<String>>()
-                        .join(",");
-                    let cycle_str: String = retraversal.get_retraversal_str();
+            let header = retraversal_header + &statistics_header + &ranking_header;
+            file.write_all(format!("{}\n", header).as_bytes())?;
+            let row = |retraversal: &Cycle<usize>| {
+                let locality_str = locality_calc(retraversal)
+                    .iter()
+                    .map(|x| x.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",");
+                let cycle_str: String = retraversal.get_retraversal_str();
+                let statistics_str: String = statistics
+                    .iter()
+                    .map(|stat| stat.evaluate(retraversal) + ",")
+                    .collect();
 
-                    format!(
-                        "{},\"{}\",{}\n",
-                        retraversal.inversions(),
-                        cycle_str,
-                        locality_str
-                    )
-                })
-                .collect();
-            let out = format!("{}\n{}", header, text);
-            file.write_all(out.as_bytes())?
+                format!(
+                    "{},\"{}\",{}{}\n",
+                    retraversal.inversions(),
+                    cycle_str,
+                    statistics_str,
+                    locality_str
+                )
+            };
+            if sorted {
+                // sorting needs every element at once
+                let mut set = group.elements().collect::<Vec<_>>();
+                set.sort_unstable_by_key(|cycle| cycle.inversions());
+                for chunk in set.chunks(PLOT_CHUNK_SIZE) {
+                    let text: String = chunk.par_iter().map(row).collect();
+                    file.write_all(text.as_bytes())?
+                }
+            } else {
+                // each batch is written before the next is listed
+                in_batches(
+                    &*group,
+                    |mut text: String, cycle| {
+                        text.push_str(&row(&cycle));
+                        text
+                    },
+                    |a, b| a + &b,
+                    |text| file.write_all(text.as_bytes()),
+                )?;
+            }
         }
         Commands::FindChain {
             symmetric_n,
             locality_calculator,
             cache_capacity_rankings,
             start,
+            start_rank,
             max_length,
+            generators,
+            generator,
             output_file,
         } => {
             assert_ne!(
@@ -215,8 +602,55 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 File::create("./output")?
             };
             let group = sym(symmetric_n);
+            let generating_set = if generator.is_empty() {
+                GeneratingSet::from(&generators)
+            } else {
+                let generators: Vec<Cycle<usize>> = generator
+                    .iter()
+                    .map(|g| {
+                        Cycle::parse(g, group.ground_set()).unwrap_or_else(|e| {
+                            Cli::command()
+                                .error(
+                                    ErrorKind::InvalidValue,
+                                    format!("invalid --generator: {}", e),
+                                )
+                                .exit()
+                        })
+                    })
+                    .collect();
+                // lengths are distances in the Cayley graph, which has to reach all of S_n
+                if PermutationGroup::new(generators.clone(), group.ground_set()).order()
+                    != group.order()
+                {
+                    Cli::command()
+                        .error(
+                            ErrorKind::InvalidValue,
+                            format!(
+                                "invalid --generator: {} don't generate S_{}",
+                                generator.join(" "),
+                                symmetric_n
+                            ),
+                        )
+                        .exit()
+                }
+                GeneratingSet::Custom(generators)
+            };
+            let group = group.with_generating_set(generating_set);
             let starting = if let Some(s) = start {
-                group.create_retraversal(&s)
+                Cycle::parse(&s, group.ground_set()).unwrap_or_else(|e| {
+                    Cli::command()
+                        .error(ErrorKind::InvalidValue, format!("invalid --start: {}", e))
+                        .exit()
+                })
+            } else if let Some(k) = start_rank {
+                group.try_unrank(k).unwrap_or_else(|| {
+                    Cli::command()
+                        .error(
+                            ErrorKind::InvalidValue,
+                            format!("invalid --start-rank: {} is not below {}!", k, symmetric_n),
+                        )
+                        .exit()
+                })
             } else {
                 group.identity()
             };
@@ -263,6 +697,48 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let serialized = serde_json::to_string_pretty(&data).unwrap();
             file.write_all(serialized.as_bytes())?;
         }
+        Commands::Trace {
+            symmetric_n,
+            expression,
+            cache_capacity_rankings,
+            output_file,
+        } => {
+            let ground: Vec<usize> = (1..=symmetric_n).collect();
+            let generator = TraceExpression::parse(&expression, ground).unwrap_or_else(|e| {
+                Cli::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        format!("invalid expression: {}", e),
+                    )
+                    .exit()
+            });
+            let file = if let Some(o) = output_file {
+                File::create(o)?
+            } else {
+                File::create("./output")?
+            };
+            let mut out = BufWriter::new(file);
+            if cache_capacity_rankings.is_empty() {
+                let mut lines = Lines(out);
+                for access in generator.accesses() {
+                    lines.push(access)?;
+                }
+                lines.0.flush()?;
+                return Ok(());
+            }
+            let mut caches: Vec<LruHits<usize>> = cache_capacity_rankings
+                .iter()
+                .map(|&c| LruHits::new(c))
+                .collect();
+            for access in generator.accesses() {
+                caches.iter_mut().for_each(|cache| cache.access(access));
+            }
+            out.write_all("\"cache_size\",\"hits\"\n".as_bytes())?;
+            for (c, cache) in cache_capacity_rankings.iter().zip(caches.iter()) {
+                out.write_all(format!("{},{}\n", c, cache.hits()).as_bytes())?;
+            }
+            out.flush()?;
+        }
         Commands::Simulate { .. } => todo!(),
     }
     /*
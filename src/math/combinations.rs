@@ -5,3 +5,67 @@ pub fn factorial(num: i128) -> i128 {
 pub fn combinations(n: i128, k: i128) -> i128 {
     (factorial(n) / factorial(k)) / factorial(n - k)
 }
+
+/// Writes k in the factorial number system with n digits, most significant first, so the i-th digit is less than n - i.
+/// Returns None if k doesn't fit, ie k >= n!.
+pub fn to_factoradic(k: u128, n: usize) -> Option<Vec<usize>> {
+    let mut k = k;
+    let mut digits = Vec::with_capacity(n);
+    for radix in 1..=n as u128 {
+        digits.push((k % radix) as usize);
+        k /= radix;
+    }
+    digits.reverse();
+    match k {
+        0 => Some(digits),
+        _ => None,
+    }
+}
+
+/// The inverse of to_factoradic. Returns None if the value overflows a u128, which happens past 34 digits.
+pub fn from_factoradic(digits: &[usize]) -> Option<u128> {
+    let n = digits.len();
+    digits.iter().enumerate().try_fold(0u128, |acc, (i, &d)| {
+        acc.checked_mul((n - i) as u128)?.checked_add(d as u128)
+    })
+}
+
+/// n! if it fits in a u128, which it does up to n = 34.
+pub fn checked_factorial(n: usize) -> Option<u128> {
+    (1..=n as u128).try_fold(1u128, |acc, i| acc.checked_mul(i))
+}
+
+/// Rearranges the slice into the next permutation in lexicographic order, returning false (and leaving it
+/// sorted again) once it was the last one.
+pub fn next_permutation(data: &mut [u32]) -> bool {
+    let Some(i) = (1..data.len()).rev().find(|&i| data[i - 1] < data[i]) else {
+        data.reverse();
+        return false;
+    };
+    let j = (i..data.len())
+        .rev()
+        .find(|&j| data[j] > data[i - 1])
+        .unwrap();
+    data.swap(i - 1, j);
+    data[i..].reverse();
+    true
+}
+
+/// The partitions of n, each with its parts from largest to smallest, in reverse lexicographic order: [n] first and
+/// [1, 1, ..., 1] last. These are the cycle types of S_n.
+pub fn partitions(n: usize) -> Vec<Vec<usize>> {
+    fn extend(rest: usize, largest: usize, prefix: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        if rest == 0 {
+            out.push(prefix.clone());
+            return;
+        }
+        for part in (1..=largest.min(rest)).rev() {
+            prefix.push(part);
+            extend(rest - part, part, prefix, out);
+            prefix.pop();
+        }
+    }
+    let mut out = Vec::new();
+    extend(n, n, &mut Vec::new(), &mut out);
+    out
+}